@@ -1,43 +1,124 @@
-use crate::{canvas::Canvas, position::IndexType, position::Point, sprite::Sprite, timer::Timer, point_set::PointSet};
-use std::{cmp::max, collections::HashSet, time::Duration};
+use crate::{bit_matrix::BitMatrix, canvas::Canvas, position::IndexType, position::Point, sprite::Sprite, sprite_parser::{DslError, DslParser}, point_set::PointSet};
+use std::{cmp::max, collections::HashSet, rc::Rc, time::Duration};
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayMode {
+    // to the last frame and stays there
+    Once,
+    // from the last frame back to the first one
+    Loop,
+    // there and back, the first and the last frames are shown once per loop
+    PingPong,
+}
+
+// what happened during one update
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct AnimationEvents {
+    pub frame_changed: bool,
+    pub loops_completed: u32,
+    pub finished: bool,
+}
+
+impl AnimationEvents {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/*
+    The frames are shared between the clones of an animation,
+    a clone copies them only when it changes them.
+*/
+#[derive(Clone)]
 pub struct Animated {
-    sprites: Vec<Sprite>,
+    sprites: Rc<Vec<Sprite>>,
     max_pos: Point,
+    // the frame on the screen
     id: usize,
-    delay: Timer,
-    looped: bool,
+    // the place in the playback order, it is not the frame for reversed animations
+    pos: usize,
+    // the time spent on the current frame
+    elapsed: Duration,
+    // the default frame duration, frames may override it
+    switch_delay: Duration,
+    delays: Rc<Vec<Option<Duration>>>,
+    mode: PlayMode,
+    reverse: bool,
+    // None plays forever, Once is always one loop
+    play_count: Option<u32>,
+    loops: u32,
+    // the direction of the ping-pong
+    backward: bool,
+    finished: bool,
 }
 
 impl Animated {
-    pub fn new(switch_delay: Duration) -> Self {
+    fn new_with_mode(switch_delay: Duration, mode: PlayMode) -> Self {
         Self {
-            sprites: Vec::new(),
+            sprites: Rc::new(Vec::new()),
             max_pos: Point::new(0, 0),
             id: 0,
-            delay: Timer::new(switch_delay),
-            looped: false,
+            pos: 0,
+            elapsed: Duration::ZERO,
+            switch_delay,
+            delays: Rc::new(Vec::new()),
+            mode,
+            reverse: false,
+            play_count: None,
+            loops: 0,
+            backward: false,
+            finished: false,
         }
     }
 
+    pub fn new(switch_delay: Duration) -> Self {
+        Self::new_with_mode(switch_delay, PlayMode::Once)
+    }
+
     pub fn new_static() -> Self {
-        Self {
-            sprites: Vec::new(),
-            max_pos: Point::new(0, 0),
-            id: 0,
-            delay: Timer::new(Duration::MAX),
-            looped: false,
-        }
+        Self::new_with_mode(Duration::MAX, PlayMode::Once)
     }
 
     pub fn new_looped(switch_delay: Duration) -> Self {
-        Self {
-            sprites: Vec::new(),
-            max_pos: Point::new(0, 0),
-            id: 0,
-            delay: Timer::new(switch_delay),
-            looped: true,
-        }
+        Self::new_with_mode(switch_delay, PlayMode::Loop)
+    }
+
+    pub fn new_ping_pong(switch_delay: Duration) -> Self {
+        Self::new_with_mode(switch_delay, PlayMode::PingPong)
+    }
+
+    pub fn set_play_mode(&mut self, mode: PlayMode) {
+        self.mode = mode;
+        self.restart();
+    }
+
+    pub fn get_play_mode(&self) -> PlayMode {
+        self.mode
+    }
+
+    // plays the frames from the last one
+    pub fn set_reverse(&mut self, value: bool) {
+        self.reverse = value;
+        self.restart();
+    }
+
+    pub fn is_reverse(&self) -> bool {
+        self.reverse
+    }
+
+    // how many loops Loop and PingPong play, None is forever
+    pub fn set_play_count(&mut self, count: Option<u32>) {
+        self.play_count = count;
+        self.restart();
+    }
+
+    pub fn get_play_count(&self) -> Option<u32> {
+        self.play_count
+    }
+
+    // counts the loops from now on, the frame stays
+    pub fn reset_loops(&mut self) {
+        self.loops = 0;
     }
 
     pub fn add_from_string(&mut self, mut s: &str) {
@@ -48,50 +129,240 @@ impl Animated {
         }
     }
 
+    // the strict version of add_from_string, nothing is added on errors
+    pub fn try_add_from_string(&mut self, s: &str, parser: &DslParser) -> Result<(), DslError> {
+        for sprite in parser.parse_frames(s)? {
+            self.add_sprite(sprite);
+        }
+        Ok(())
+    }
+
     pub fn add_sprite(&mut self, sprite: Sprite) {
         self.max_pos.x = max(sprite.get_max().x, self.max_pos.x);
         self.max_pos.y = max(sprite.get_max().y, self.max_pos.y);
-        self.sprites.push(sprite);
+        Rc::make_mut(&mut self.sprites).push(sprite);
+        Rc::make_mut(&mut self.delays).push(None);
+        // the first frame of a reversed animation is the last one
+        self.id = self.get_frame_id(self.pos);
+    }
+
+    // adds a frame that is shown for its own duration instead of the default one
+    pub fn add_sprite_with_delay(&mut self, sprite: Sprite, delay: Duration) {
+        self.add_sprite(sprite);
+        if let Some(last) = Rc::make_mut(&mut self.delays).last_mut() {
+            *last = Some(delay);
+        }
+    }
+
+    pub fn get_frame_delay(&self, id: usize) -> Duration {
+        self.delays
+            .get(id)
+            .copied()
+            .flatten()
+            .unwrap_or(self.switch_delay)
+    }
+
+    /*
+        Takes the frames of the other animation, the playback goes on from the same place.
+        Both share the frames, nothing is copied.
+    */
+    pub fn set_frames_from(&mut self, other: &Animated) {
+        self.sprites = other.sprites.clone();
+        self.delays = other.delays.clone();
+        self.max_pos = other.max_pos;
+        if self.pos >= self.sprites.len() {
+            self.pos = 0;
+        }
+        self.id = self.get_frame_id(self.pos);
+    }
+
+    // whether both show the same frames
+    pub fn shares_frames(&self, other: &Animated) -> bool {
+        Rc::ptr_eq(&self.sprites, &other.sprites)
+    }
+
+    pub fn get_frames_count(&self) -> usize {
+        self.sprites.len()
+    }
+
+    // the anchor of the current frame
+    pub fn get_anchor(&self, name: &str) -> Option<Point> {
+        self.get_current_sprite().and_then(|sprite| sprite.get_anchor(name))
+    }
+
+    // sets the anchor on every frame
+    pub fn set_anchor(&mut self, name: &str, point: Point) {
+        for sprite in Rc::make_mut(&mut self.sprites).iter_mut() {
+            sprite.set_anchor(name, point);
+        }
+    }
+
+    /*
+        Moves the playback by delta, a long delta can pass several frames.
+        Static and finished animations do not move.
+    */
+    pub fn update(&mut self, delta: Duration) -> AnimationEvents {
+        let mut events = AnimationEvents::default();
+        if self.sprites.is_empty() || self.is_static() || self.finished {
+            return events;
+        }
+        self.elapsed += delta;
+        loop {
+            let delay = self.get_frame_delay(self.id);
+            if self.elapsed < delay {
+                break;
+            }
+            self.elapsed -= delay;
+            let last = self.id;
+            self.step(&mut events);
+            events.frame_changed |= self.id != last;
+            // zero delays move one frame per update
+            if self.finished || delay.is_zero() {
+                break;
+            }
+        }
+        if self.finished {
+            self.elapsed = Duration::ZERO;
+        }
+        events
     }
 
-    pub fn update(&mut self, delta: Duration) {
-        if self.sprites.is_empty() || self.is_static() {
-            return;
+    /*
+        The next frame right away, as if its delay has passed, static animations move too.
+        Stepping past the end starts again from the first frame in the same call,
+        the events still tell that the animation finished.
+    */
+    pub fn update_force(&mut self) -> AnimationEvents {
+        let mut events = AnimationEvents::default();
+        if self.sprites.is_empty() {
+            return events;
+        }
+        let last = self.id;
+        if !self.finished {
+            self.step(&mut events);
         }
-        if self.delay.update(delta) {
-            if !self.is_done() {
-                self.id = (self.id + 1) % self.sprites.len();
-                self.delay.reset();
+        if self.finished {
+            self.restart();
+        }
+        self.elapsed = Duration::ZERO;
+        events.frame_changed |= self.id != last;
+        events
+    }
+
+    fn step(&mut self, events: &mut AnimationEvents) {
+        let count = self.sprites.len();
+        match self.mode {
+            PlayMode::PingPong if count > 1 => {
+                if !self.backward {
+                    if self.pos + 1 < count {
+                        self.pos += 1;
+                    } else {
+                        self.backward = true;
+                        self.pos -= 1;
+                    }
+                } else if self.pos > 0 {
+                    self.pos -= 1;
+                } else if self.complete_loop(events) {
+                    self.backward = false;
+                    self.pos = 1;
+                }
+            }
+            _ => {
+                if self.pos + 1 < count {
+                    self.pos += 1;
+                } else if self.complete_loop(events) {
+                    self.pos = 0;
+                }
             }
         }
+        self.id = self.get_frame_id(self.pos);
     }
 
-    pub fn update_force(&mut self) {
-        if !self.sprites.is_empty() {
-            self.id = (self.id + 1) % self.sprites.len();
-            self.delay.reset();
+    // false when that was the last loop
+    fn complete_loop(&mut self, events: &mut AnimationEvents) -> bool {
+        self.loops += 1;
+        events.loops_completed += 1;
+        let count = match self.mode {
+            PlayMode::Once => Some(1),
+            _ => self.play_count,
+        };
+        if count.is_some_and(|count| self.loops >= count) {
+            self.finished = true;
+            events.finished = true;
+            return false;
         }
+        true
+    }
+
+    fn get_frame_id(&self, pos: usize) -> usize {
+        if self.reverse && !self.sprites.is_empty() {
+            self.sprites.len() - 1 - pos
+        } else {
+            pos
+        }
+    }
+
+    // the frames separated by '-', see Sprite::to_dsl_string
+    pub fn to_dsl_string(&self) -> String {
+        self.sprites
+            .iter()
+            .map(|s| s.to_dsl_string())
+            .collect::<Vec<_>>()
+            .join("-\n")
     }
 
-    fn is_static(&self) -> bool {
-        self.delay.is_max_duration()
+    pub fn is_looped(&self) -> bool {
+        self.mode != PlayMode::Once
     }
 
-    fn end(&self) -> bool {
-        self.sprites.is_empty() || self.id == (self.sprites.len() - 1)
+    pub fn get_default_delay(&self) -> Duration {
+        self.switch_delay
     }
 
-    fn is_done(&self) -> bool {
-        !self.looped && self.end() && self.delay.ready()
+    // Duration::MAX makes the animation static
+    pub fn set_default_delay(&mut self, delay: Duration) {
+        self.switch_delay = delay;
+        self.elapsed = Duration::ZERO;
     }
 
+    // None is the default delay
+    pub fn get_frame_delays(&self) -> &[Option<Duration>] {
+        &self.delays
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.switch_delay == Duration::MAX
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn get_frame(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_loops(&self) -> u32 {
+        self.loops
+    }
+
+    // starts a finished animation again
     pub fn reset(&mut self) {
-        if self.is_done() {
-            self.delay.reset();
-            self.id = 0;
+        if self.finished {
+            self.restart();
         }
     }
 
+    // back to the first frame of the playback
+    pub fn restart(&mut self) {
+        self.pos = 0;
+        self.id = self.get_frame_id(0);
+        self.elapsed = Duration::ZERO;
+        self.loops = 0;
+        self.backward = false;
+        self.finished = false;
+    }
+
     pub fn get_current_sprite(&self) -> Option<&Sprite> {
         if self.sprites.is_empty() {
             None
@@ -101,8 +372,77 @@ impl Animated {
     }
 
     pub fn rotate_90(&mut self) {
-        for sprite in self.sprites.iter_mut() {
-            sprite.rotate_90();
+        self.for_each_sprite(|s| s.rotate_90());
+    }
+
+    pub fn rotate_180(&mut self) {
+        self.for_each_sprite(|s| s.rotate_180());
+    }
+
+    pub fn rotate_270(&mut self) {
+        self.for_each_sprite(|s| s.rotate_270());
+    }
+
+    pub fn rotate_quarters(&mut self, turns: i32) {
+        self.for_each_sprite(|s| s.rotate_quarters(turns));
+    }
+
+    pub fn flip_horizontal(&mut self) {
+        self.for_each_sprite(|s| s.flip_horizontal());
+    }
+
+    pub fn flip_vertical(&mut self) {
+        self.for_each_sprite(|s| s.flip_vertical());
+    }
+
+    pub fn scale_up(&mut self, factor: IndexType) {
+        self.for_each_sprite(|s| s.scale_up(factor));
+    }
+
+    pub fn scale_down(&mut self, factor: IndexType) {
+        self.for_each_sprite(|s| s.scale_down(factor));
+    }
+
+    pub fn rotate(&mut self, degrees: f32) {
+        self.for_each_sprite(|s| s.rotate(degrees));
+    }
+
+    pub fn shear(&mut self, kx: f32, ky: f32) {
+        self.for_each_sprite(|s| s.shear(kx, ky));
+    }
+
+    pub fn dilate(&mut self, radius: IndexType) {
+        self.for_each_sprite(|s| s.dilate(radius));
+    }
+
+    pub fn erode(&mut self, radius: IndexType) {
+        self.for_each_sprite(|s| s.erode(radius));
+    }
+
+    pub fn outline(&mut self) {
+        self.for_each_sprite(|s| s.outline());
+    }
+
+    pub fn get_sprites(&self) -> &[Sprite] {
+        &self.sprites
+    }
+
+    // all the frames on top of each other
+    pub fn get_silhouette(&self) -> Sprite {
+        let mut res = Sprite::new_with_size(self.max_pos.x + 1, self.max_pos.y + 1);
+        for sprite in self.sprites.iter() {
+            res.union(sprite, 0, 0);
+        }
+        res
+    }
+
+    // applies the call to every frame and updates the size of the animation
+    pub fn for_each_sprite<F: FnMut(&mut Sprite)>(&mut self, mut call: F) {
+        self.max_pos = Point::new(0, 0);
+        for sprite in Rc::make_mut(&mut self.sprites).iter_mut() {
+            call(sprite);
+            self.max_pos.x = max(sprite.get_max().x, self.max_pos.x);
+            self.max_pos.y = max(sprite.get_max().y, self.max_pos.y);
         }
     }
 
@@ -132,6 +472,10 @@ impl PointSet for Animated {
         }
     }
     
+    fn get_bits(&self) -> Option<&BitMatrix> {
+        self.get_current_sprite().and_then(|sprite| sprite.get_bits())
+    }
+
     fn get_max(&self) -> Point {
         if self.sprites.is_empty() {
             Point::new(0, 0)
@@ -152,3 +496,138 @@ impl PointSet for Animated {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn frames(count: usize, mut animated: Animated) -> Animated {
+        for _ in 0..count {
+            animated.add_sprite(Sprite::new());
+        }
+        animated
+    }
+
+    // the frames shown after every update
+    fn play(animated: &mut Animated, delta: Duration, updates: usize) -> Vec<usize> {
+        (0..updates)
+            .map(|_| {
+                animated.update(delta);
+                animated.get_frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn short_deltas_add_up() {
+        let mut animated = frames(3, Animated::new_looped(10 * MS));
+        assert_eq!(play(&mut animated, 4 * MS, 6), vec![0, 0, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn a_long_delta_passes_several_frames() {
+        let mut animated = frames(4, Animated::new_looped(10 * MS));
+        let events = animated.update(25 * MS);
+        assert_eq!(animated.get_frame(), 2);
+        assert!(events.frame_changed);
+        assert_eq!(events.loops_completed, 0);
+        // the rest of the delta stays for the next update
+        animated.update(5 * MS);
+        assert_eq!(animated.get_frame(), 3);
+        // a whole loop and a bit more in one update
+        let events = animated.update(45 * MS);
+        assert_eq!(animated.get_frame(), 3);
+        assert_eq!(events.loops_completed, 1);
+    }
+
+    #[test]
+    fn frame_delays_override_the_default() {
+        let mut animated = Animated::new_looped(10 * MS);
+        animated.add_sprite(Sprite::new());
+        animated.add_sprite_with_delay(Sprite::new(), 30 * MS);
+        animated.add_sprite(Sprite::new());
+        assert_eq!(play(&mut animated, 10 * MS, 6), vec![1, 1, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn ping_pong_shows_the_ends_once() {
+        let mut animated = frames(4, Animated::new_ping_pong(MS));
+        assert_eq!(play(&mut animated, MS, 9), vec![1, 2, 3, 2, 1, 0, 1, 2, 3]);
+        assert_eq!(animated.get_loops(), 1);
+    }
+
+    #[test]
+    fn reversed_ping_pong_starts_from_the_last_frame() {
+        let mut animated = frames(3, Animated::new_ping_pong(MS));
+        animated.set_reverse(true);
+        assert_eq!(animated.get_frame(), 2);
+        assert_eq!(play(&mut animated, MS, 5), vec![1, 0, 1, 2, 1]);
+    }
+
+    #[test]
+    fn once_stops_at_the_last_frame() {
+        let mut animated = frames(3, Animated::new(MS));
+        let events: Vec<AnimationEvents> = (0..4).map(|_| animated.update(MS)).collect();
+        assert_eq!(animated.get_frame(), 2);
+        assert!(animated.is_finished());
+        assert!(events[1].frame_changed && !events[1].finished);
+        assert!(!events[2].frame_changed && events[2].finished);
+        assert_eq!(events[2].loops_completed, 1);
+        // nothing happens after the end
+        assert!(events[3].is_empty());
+    }
+
+    #[test]
+    fn play_count_ends_the_loops() {
+        let mut animated = frames(2, Animated::new_looped(MS));
+        animated.set_play_count(Some(2));
+        let events = animated.update(10 * MS);
+        assert_eq!(events.loops_completed, 2);
+        assert!(events.finished);
+        assert_eq!(animated.get_frame(), 1);
+        assert!(animated.update(10 * MS).is_empty());
+    }
+
+    #[test]
+    fn ping_pong_count_ends_on_the_first_frame() {
+        let mut animated = frames(3, Animated::new_ping_pong(MS));
+        animated.set_play_count(Some(1));
+        assert_eq!(play(&mut animated, MS, 5), vec![1, 2, 1, 0, 0]);
+        assert!(animated.is_finished());
+        assert_eq!(animated.get_loops(), 1);
+    }
+
+    #[test]
+    fn static_animations_do_not_move() {
+        let mut animated = frames(3, Animated::new_static());
+        assert!(animated.update(Duration::from_secs(1000)).is_empty());
+        assert_eq!(animated.get_frame(), 0);
+    }
+
+    #[test]
+    fn update_force_follows_the_play_mode() {
+        let mut animated = frames(3, Animated::new_ping_pong(10 * MS));
+        let forced: Vec<usize> = (0..5)
+            .map(|_| {
+                animated.update_force();
+                animated.get_frame()
+            })
+            .collect();
+        assert_eq!(forced, vec![1, 2, 1, 0, 1]);
+    }
+
+    #[test]
+    fn update_force_wraps_without_a_stall() {
+        let mut animated = frames(3, Animated::new_static());
+        let forced: Vec<(usize, bool)> = (0..4)
+            .map(|_| {
+                let events = animated.update_force();
+                (animated.get_frame(), events.finished)
+            })
+            .collect();
+        assert_eq!(forced, vec![(1, false), (2, false), (0, true), (1, false)]);
+        assert!(!animated.is_finished());
+    }
+}
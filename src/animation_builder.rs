@@ -1,5 +1,9 @@
 use std::time::Duration;
-use crate::{animated::Animated, sprite::Sprite};
+use crate::{
+    animated::{Animated, PlayMode},
+    sprite::Sprite,
+    sprite_parser::{DslError, DslParser},
+};
 
 pub struct AnimationBuilder {
     value: Animated,
@@ -24,11 +28,39 @@ impl AnimationBuilder {
         }
     }
 
+    pub fn new_ping_pong(switch_delay: Duration) -> Self {
+        Self {
+            value: Animated::new_ping_pong(switch_delay),
+        }
+    }
+
+    pub fn with_play_mode(mut self, mode: PlayMode) -> Self {
+        self.value.set_play_mode(mode);
+        self
+    }
+
+    pub fn with_reverse(mut self, value: bool) -> Self {
+        self.value.set_reverse(value);
+        self
+    }
+
+    pub fn with_play_count(mut self, count: Option<u32>) -> Self {
+        self.value.set_play_count(count);
+        self
+    }
+
     pub fn add_sprite(mut self, s: Sprite) -> Self {
         self.value.add_sprite(s);
         self
     }
 
+    pub fn add_sprites<I: IntoIterator<Item = Sprite>>(mut self, sprites: I) -> Self {
+        for s in sprites {
+            self.value.add_sprite(s);
+        }
+        self
+    }
+
     pub fn add_sprite_from_string(mut self, s: &str) -> Self {
         self.value.add_sprite(Sprite::new_from_string(s));
         self
@@ -39,6 +71,11 @@ impl AnimationBuilder {
         self
     }
 
+    pub fn try_add_from_string(mut self, s: &str, parser: &DslParser) -> Result<Self, DslError> {
+        self.value.try_add_from_string(s, parser)?;
+        Ok(self)
+    }
+
     pub fn modify<F>(mut self, call: F) -> Self 
     where 
         F: Fn(Animated) -> Animated
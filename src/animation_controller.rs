@@ -0,0 +1,457 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{
+    animated::{Animated, AnimationEvents},
+    bit_matrix::BitMatrix,
+    point_set::PointSet,
+    position::Point,
+    sprite::ANCHOR_PIVOT,
+};
+
+/*
+    The animations of one game object and the rules between them.
+    The object requests a state and the controller decides whether it can go there
+    and how the old animation leaves the screen.
+
+    Without transitions every state can be requested from any other one,
+    once a transition is added only the added ones are allowed.
+    A state with a next state goes there by itself when its animation finishes.
+    The rules are shared between the clones, a clone of a set up controller
+    is a cheap start for the objects of the same kind.
+    The old frame of an overlap is only drawn, the collisions see the current one.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AnimationState {
+    Idle,
+    Move,
+    Fire,
+    Explode,
+    Destroyed,
+}
+
+impl AnimationState {
+    pub const ALL: [AnimationState; 5] = [
+        AnimationState::Idle,
+        AnimationState::Move,
+        AnimationState::Fire,
+        AnimationState::Explode,
+        AnimationState::Destroyed,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Blend {
+    // the new animation starts right away
+    Cut,
+    // the old animation ends its loop first, static and finished ones are cut
+    FinishLoop,
+    // both animations are drawn for a while, aligned by their pivots
+    Overlap(Duration),
+}
+
+#[derive(Clone, Default)]
+struct Rules {
+    transitions: HashSet<(AnimationState, AnimationState)>,
+    next: HashMap<AnimationState, AnimationState>,
+    blends: HashMap<(AnimationState, AnimationState), Blend>,
+}
+
+#[derive(Clone)]
+pub struct AnimationController {
+    // indexed by AnimationState::index
+    states: [Option<Animated>; AnimationState::ALL.len()],
+    current: AnimationState,
+    rules: Rc<Rules>,
+    // waits for the end of the loop of the current state
+    pending: Option<AnimationState>,
+    // the old state and how long it stays on the screen
+    fading: Option<(AnimationState, Duration)>,
+}
+
+impl AnimationController {
+    pub fn new(state: AnimationState, animated: Animated) -> Self {
+        let mut states: [Option<Animated>; AnimationState::ALL.len()] = Default::default();
+        states[state.index()] = Some(animated);
+        Self {
+            states,
+            current: state,
+            rules: Rc::new(Rules::default()),
+            pending: None,
+            fading: None,
+        }
+    }
+
+    pub fn with_state(mut self, state: AnimationState, animated: Animated) -> Self {
+        self.states[state.index()] = Some(animated);
+        self
+    }
+
+    // allows requesting `to` while `from` plays
+    pub fn with_transition(mut self, from: AnimationState, to: AnimationState) -> Self {
+        Rc::make_mut(&mut self.rules).transitions.insert((from, to));
+        self
+    }
+
+    // goes to `to` when `from` finishes
+    pub fn with_next(mut self, from: AnimationState, to: AnimationState) -> Self {
+        Rc::make_mut(&mut self.rules).next.insert(from, to);
+        self
+    }
+
+    // Blend::Cut is the default
+    pub fn with_blend(mut self, from: AnimationState, to: AnimationState, blend: Blend) -> Self {
+        Rc::make_mut(&mut self.rules).blends.insert((from, to), blend);
+        self
+    }
+
+    // whether both follow the same rules
+    pub fn shares_rules(&self, other: &AnimationController) -> bool {
+        Rc::ptr_eq(&self.rules, &other.rules)
+    }
+
+    pub fn get_state(&self) -> AnimationState {
+        self.current
+    }
+
+    pub fn has_state(&self, state: AnimationState) -> bool {
+        self.states[state.index()].is_some()
+    }
+
+    pub fn get_animation(&self, state: AnimationState) -> Option<&Animated> {
+        self.states[state.index()].as_ref()
+    }
+
+    pub fn get_current(&self) -> &Animated {
+        self.get_animation(self.current).expect("the current state has an animation")
+    }
+
+    fn get_current_mut(&mut self) -> &mut Animated {
+        self.states[self.current.index()]
+            .as_mut()
+            .expect("the current state has an animation")
+    }
+
+    pub fn can_request(&self, state: AnimationState) -> bool {
+        let transitions = &self.rules.transitions;
+        self.has_state(state)
+            && (transitions.is_empty() || transitions.contains(&(self.current, state)))
+    }
+
+    /*
+        True when the state is changed or waits for the end of the loop.
+        Requesting the current state keeps it, a looped state counts its loops from now.
+    */
+    pub fn request(&mut self, state: AnimationState) -> bool {
+        if state == self.current {
+            self.pending = None;
+            self.get_current_mut().reset_loops();
+            return false;
+        }
+        if !self.can_request(state) {
+            return false;
+        }
+        self.switch_to(state);
+        true
+    }
+
+    pub fn update(&mut self, delta: Duration) -> AnimationEvents {
+        let events = self.get_current_mut().update(delta);
+        if let Some((state, left)) = self.fading {
+            if let Some(animated) = self.states[state.index()].as_mut() {
+                animated.update(delta);
+            }
+            self.fading = if left > delta { Some((state, left - delta)) } else { None };
+        }
+        self.follow(events);
+        events
+    }
+
+    // the next frame of the current state right away, see Animated::update_force
+    pub fn update_force(&mut self) -> AnimationEvents {
+        let events = self.get_current_mut().update_force();
+        self.follow(events);
+        events
+    }
+
+    // the pending and the next states after the current animation moved
+    fn follow(&mut self, events: AnimationEvents) {
+        if let Some(state) = self.pending {
+            if events.loops_completed > 0 || events.finished {
+                self.enter(state);
+            }
+        } else if events.finished {
+            if let Some(state) = self.rules.next.get(&self.current).copied() {
+                self.switch_to(state);
+            }
+        }
+    }
+
+    // the same for every state, like turning the object
+    pub fn for_each_animation<F: FnMut(&mut Animated)>(&mut self, mut call: F) {
+        self.for_each_state(|_, animated| call(animated));
+    }
+
+    pub fn for_each_state<F: FnMut(AnimationState, &mut Animated)>(&mut self, mut call: F) {
+        for (state, animated) in AnimationState::ALL.iter().zip(self.states.iter_mut()) {
+            if let Some(animated) = animated {
+                call(*state, animated);
+            }
+        }
+    }
+
+    pub fn rotate_quarters(&mut self, turns: i32) {
+        self.for_each_animation(|a| a.rotate_quarters(turns));
+    }
+
+    // the anchor of the current frame
+    pub fn get_anchor(&self, name: &str) -> Option<Point> {
+        self.get_current().get_anchor(name)
+    }
+
+    fn switch_to(&mut self, state: AnimationState) {
+        let blend = self
+            .rules
+            .blends
+            .get(&(self.current, state))
+            .copied()
+            .unwrap_or(Blend::Cut);
+        let current = self.get_current();
+        match blend {
+            Blend::FinishLoop if !current.is_static() && !current.is_finished() => {
+                self.pending = Some(state);
+            }
+            Blend::Overlap(time) if !time.is_zero() => {
+                let from = self.current;
+                self.enter(state);
+                self.fading = Some((from, time));
+            }
+            _ => self.enter(state),
+        }
+    }
+
+    fn enter(&mut self, state: AnimationState) {
+        self.pending = None;
+        self.fading = None;
+        self.current = state;
+        self.get_current_mut().restart();
+    }
+
+    // the old animation of an overlap and where its frame is, the pivots are on each other
+    pub fn get_fading(&self) -> Option<(&Animated, Point)> {
+        let (state, _) = self.fading?;
+        let old = self.get_animation(state)?;
+        let offset = match (self.get_anchor(ANCHOR_PIVOT), old.get_anchor(ANCHOR_PIVOT)) {
+            (Some(a), Some(b)) => Point::new(a.x - b.x, a.y - b.y),
+            _ => Point::new(0, 0),
+        };
+        Some((old, offset))
+    }
+}
+
+impl PointSet for AnimationController {
+    fn get_point_set(&self) -> Option<&HashSet<Point>> {
+        self.get_current().get_point_set()
+    }
+
+    fn get_bits(&self) -> Option<&BitMatrix> {
+        self.get_current().get_bits()
+    }
+
+    fn get_max(&self) -> Point {
+        self.get_current().get_max()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.get_current().is_empty()
+    }
+
+    // the fading frame goes first, it may stick out of the box on any side
+    fn for_each_drawn(&self, call: &mut dyn FnMut(Point)) {
+        if let Some((old, offset)) = self.get_fading() {
+            old.for_each_drawn(&mut |p| call(Point::new(p.x + offset.x, p.y + offset.y)));
+        }
+        self.get_current().for_each_drawn(call);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{animated::PlayMode, canvas::Canvas, position::IndexType, sprite::Sprite};
+
+    const MS: Duration = Duration::from_millis(1);
+
+    // every frame has a single dot, (id, 0) for the frame id
+    fn frames(count: usize, mode: PlayMode) -> Animated {
+        let mut animated = Animated::new(10 * MS);
+        animated.set_play_mode(mode);
+        for id in 0..count {
+            let mut sprite = Sprite::new_with_size(4, 4);
+            sprite.draw_dot(id as IndexType, 0);
+            animated.add_sprite(sprite);
+        }
+        animated
+    }
+
+    fn starting(state: AnimationState, animated: Animated) -> AnimationController {
+        AnimationController::new(state, animated)
+    }
+
+    fn drawn(controller: &AnimationController) -> Vec<Point> {
+        let mut res = Vec::new();
+        controller.for_each_drawn(&mut |p| res.push(p));
+        res
+    }
+
+    #[test]
+    fn any_state_without_transitions() {
+        let mut controller = starting(AnimationState::Idle, frames(1, PlayMode::Loop))
+            .with_state(AnimationState::Move, frames(1, PlayMode::Loop))
+            .with_state(AnimationState::Fire, frames(1, PlayMode::Once));
+        assert!(controller.request(AnimationState::Move));
+        assert!(controller.request(AnimationState::Fire));
+        assert!(controller.request(AnimationState::Idle));
+        // no animation, no state
+        assert!(!controller.request(AnimationState::Explode));
+        assert_eq!(controller.get_state(), AnimationState::Idle);
+    }
+
+    #[test]
+    fn transitions_are_an_allow_list() {
+        let mut controller = starting(AnimationState::Move, frames(1, PlayMode::Loop))
+            .with_state(AnimationState::Idle, frames(1, PlayMode::Loop))
+            .with_state(AnimationState::Explode, frames(1, PlayMode::Once))
+            .with_transition(AnimationState::Move, AnimationState::Explode);
+        assert!(!controller.can_request(AnimationState::Idle));
+        assert!(!controller.request(AnimationState::Idle));
+        assert_eq!(controller.get_state(), AnimationState::Move);
+        assert!(controller.request(AnimationState::Explode));
+        // nothing goes out of Explode
+        assert!(!controller.request(AnimationState::Move));
+        assert_eq!(controller.get_state(), AnimationState::Explode);
+    }
+
+    #[test]
+    fn requesting_the_current_state_keeps_it() {
+        let mut controller = starting(AnimationState::Idle, frames(3, PlayMode::Loop));
+        controller.update(10 * MS);
+        assert!(!controller.request(AnimationState::Idle));
+        assert_eq!(controller.get_current().get_frame(), 1);
+    }
+
+    #[test]
+    fn next_state_when_the_animation_finishes() {
+        let mut controller = starting(AnimationState::Idle, frames(2, PlayMode::Loop))
+            .with_state(AnimationState::Fire, frames(2, PlayMode::Once))
+            .with_next(AnimationState::Fire, AnimationState::Idle);
+        controller.update(10 * MS);
+        assert!(controller.request(AnimationState::Fire));
+        // the new state starts from its first frame
+        assert_eq!(controller.get_current().get_frame(), 0);
+        controller.update(10 * MS);
+        assert_eq!(controller.get_state(), AnimationState::Fire);
+        let events = controller.update(10 * MS);
+        assert!(events.finished);
+        assert_eq!(controller.get_state(), AnimationState::Idle);
+        assert_eq!(controller.get_current().get_frame(), 0);
+    }
+
+    #[test]
+    fn finish_loop_waits_for_the_end_of_the_loop() {
+        let mut controller = starting(AnimationState::Idle, frames(3, PlayMode::Loop))
+            .with_state(AnimationState::Explode, frames(2, PlayMode::Once))
+            .with_blend(AnimationState::Idle, AnimationState::Explode, Blend::FinishLoop);
+        controller.update(10 * MS);
+        assert!(controller.request(AnimationState::Explode));
+        assert_eq!(controller.get_state(), AnimationState::Idle);
+        controller.update(10 * MS);
+        assert_eq!(controller.get_state(), AnimationState::Idle);
+        assert_eq!(controller.get_current().get_frame(), 2);
+        // back to the first frame is the end of the loop
+        controller.update(10 * MS);
+        assert_eq!(controller.get_state(), AnimationState::Explode);
+        assert_eq!(controller.get_current().get_frame(), 0);
+    }
+
+    #[test]
+    fn finish_loop_cuts_a_static_state() {
+        let mut idle = Animated::new_static();
+        idle.add_sprite(Sprite::new());
+        let mut controller = starting(AnimationState::Idle, idle)
+            .with_state(AnimationState::Explode, frames(2, PlayMode::Once))
+            .with_blend(AnimationState::Idle, AnimationState::Explode, Blend::FinishLoop);
+        assert!(controller.request(AnimationState::Explode));
+        assert_eq!(controller.get_state(), AnimationState::Explode);
+    }
+
+    #[test]
+    fn requesting_the_current_state_drops_the_pending_one() {
+        let mut controller = starting(AnimationState::Idle, frames(2, PlayMode::Loop))
+            .with_state(AnimationState::Explode, frames(2, PlayMode::Once))
+            .with_blend(AnimationState::Idle, AnimationState::Explode, Blend::FinishLoop);
+        controller.request(AnimationState::Explode);
+        controller.request(AnimationState::Idle);
+        controller.update(20 * MS);
+        assert_eq!(controller.get_state(), AnimationState::Idle);
+    }
+
+    #[test]
+    fn overlap_draws_both_frames_by_their_pivots() {
+        let mut idle = frames(1, PlayMode::Loop);
+        idle.set_anchor(ANCHOR_PIVOT, Point::new(3, 2));
+        let mut moving = frames(2, PlayMode::Loop);
+        moving.set_anchor(ANCHOR_PIVOT, Point::new(1, 1));
+        let mut controller = starting(AnimationState::Idle, idle)
+            .with_state(AnimationState::Move, moving)
+            .with_blend(AnimationState::Idle, AnimationState::Move, Blend::Overlap(15 * MS));
+        assert!(controller.request(AnimationState::Move));
+        assert_eq!(controller.get_state(), AnimationState::Move);
+        // the old dot (0, 0) goes by (1 - 3, 1 - 2), out of the box of the new frame
+        assert!(drawn(&controller) == vec![Point::new(-2, -1), Point::new(0, 0)]);
+        // the collisions see only the new frame
+        assert_eq!(controller.get_bits().map(|bits| bits.count()), Some(1));
+        controller.update(10 * MS);
+        assert!(drawn(&controller) == vec![Point::new(-2, -1), Point::new(1, 0)]);
+        controller.update(10 * MS);
+        assert!(controller.get_fading().is_none());
+        assert!(drawn(&controller) == vec![Point::new(0, 0)]);
+    }
+
+    #[test]
+    fn update_force_goes_to_the_next_state() {
+        let mut controller = starting(AnimationState::Idle, frames(1, PlayMode::Loop))
+            .with_state(AnimationState::Fire, frames(2, PlayMode::Once))
+            .with_next(AnimationState::Fire, AnimationState::Idle);
+        controller.request(AnimationState::Fire);
+        controller.update_force();
+        assert_eq!(controller.get_state(), AnimationState::Fire);
+        controller.update_force();
+        assert_eq!(controller.get_state(), AnimationState::Idle);
+    }
+
+    #[test]
+    fn clones_share_the_rules_and_frames() {
+        let template = starting(AnimationState::Move, frames(1, PlayMode::Loop))
+            .with_state(AnimationState::Explode, frames(2, PlayMode::Once))
+            .with_transition(AnimationState::Move, AnimationState::Explode);
+        let mut shot = template.clone();
+        assert!(shot.shares_rules(&template));
+        let explode = |c: &AnimationController| c.get_animation(AnimationState::Explode).cloned();
+        assert!(explode(&shot).unwrap().shares_frames(&explode(&template).unwrap()));
+        // the clones play on their own
+        assert!(shot.request(AnimationState::Explode));
+        assert_eq!(template.get_state(), AnimationState::Move);
+        // a new rule makes a copy for the one that changes
+        let other = template.clone().with_next(AnimationState::Explode, AnimationState::Move);
+        assert!(!other.shares_rules(&template));
+    }
+}
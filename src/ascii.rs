@@ -0,0 +1,17 @@
+use crate::glyph_canvas::{GlyphCanvas, Glyphs};
+
+// one column, two rows of dots per cell, plain ASCII only
+pub struct Ascii;
+
+const CHARS: &[char] = &[' ', '\'', '.', ':'];
+
+impl Glyphs for Ascii {
+    const WIDTH: usize = 1;
+    const HEIGHT: usize = 2;
+
+    fn to_char(mask: u8) -> char {
+        CHARS[(mask & 0x03) as usize]
+    }
+}
+
+pub type AsciiCanvas = GlyphCanvas<Ascii>;
@@ -0,0 +1,115 @@
+use std::{
+    io::{self, Write},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+/*
+    passes everything to `inner` and records it as asciicast v2
+    (https://docs.asciinema.org/manual/asciicast/v2/) into `cast`.
+    one output event is written per flush, so a rendered frame is one event.
+    a change of the terminal size becomes a resize event.
+*/
+pub struct CastWriter<W: Write, C: Write> {
+    inner: W,
+    cast: C,
+    pending: Vec<u8>,
+    started: Instant,
+    size: (usize, usize),
+}
+
+impl<W: Write, C: Write> CastWriter<W, C> {
+    // `width` and `height` are the terminal size in cells
+    pub fn new(inner: W, mut cast: C, width: usize, height: usize) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        writeln!(
+            cast,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}}}",
+            width, height, timestamp
+        )?;
+        Ok(Self {
+            inner,
+            cast,
+            pending: Vec::new(),
+            started: Instant::now(),
+            size: (width, height),
+        })
+    }
+
+    // bytes that reached the terminal some other way, like the setup before recording
+    pub fn record(&mut self, data: &[u8]) -> io::Result<()> {
+        self.pending.extend_from_slice(data);
+        self.write_event()
+    }
+
+    // writes a resize event when the size is not the one recorded last
+    pub fn resize(&mut self, width: usize, height: usize) -> io::Result<()> {
+        if self.size == (width, height) {
+            return Ok(());
+        }
+        self.size = (width, height);
+        // the output written so far belongs to the old size
+        self.write_event()?;
+        writeln!(
+            self.cast,
+            "[{:.6}, \"r\", \"{}x{}\"]",
+            self.started.elapsed().as_secs_f64(),
+            width,
+            height
+        )?;
+        self.cast.flush()
+    }
+
+    fn write_event(&mut self) -> io::Result<()> {
+        // keep a multibyte char cut in the middle for the next event
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(s) => s.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        if valid == 0 {
+            return Ok(());
+        }
+        let data = String::from_utf8_lossy(&self.pending[..valid]).into_owned();
+        self.pending.drain(..valid);
+        writeln!(
+            self.cast,
+            "[{:.6}, \"o\", \"{}\"]",
+            self.started.elapsed().as_secs_f64(),
+            escape_json(&data)
+        )?;
+        self.cast.flush()
+    }
+}
+
+impl<W: Write, C: Write> Write for CastWriter<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.pending.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.write_event()
+    }
+}
+
+pub fn escape_json(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                res.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => res.push(c),
+        }
+    }
+    res
+}
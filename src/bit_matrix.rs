@@ -0,0 +1,552 @@
+use crate::position::{IndexType, Point};
+
+/*
+    Dense set of dots, one bit per dot, rows of u64 words.
+    Bit `x % 64` of the word `x / 64` of a row is the dot (x, y).
+    The matrix grows when a dot is set outside of it, negative dots are not stored.
+*/
+
+const WORD_BITS: usize = 64;
+
+#[derive(Clone, Default)]
+pub struct BitMatrix {
+    width: usize,
+    height: usize,
+    row_words: usize,
+    words: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn new(width: usize, height: usize) -> Self {
+        let row_words = width.div_ceil(WORD_BITS);
+        Self {
+            width,
+            height,
+            row_words,
+            words: vec![0; row_words * height],
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: IndexType, y: IndexType) -> bool {
+        match self.index(x, y) {
+            Some((id, bit)) => self.words[id] & bit != 0,
+            None => false,
+        }
+    }
+
+    pub fn set(&mut self, x: IndexType, y: IndexType) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+        let (x, y) = (x as usize, y as usize);
+        if x >= self.width || y >= self.height {
+            // doubles the size so drawing dot by dot stays cheap
+            let width = if x >= self.width { (x + 1).max(self.width * 2) } else { self.width };
+            let height = if y >= self.height { (y + 1).max(self.height * 2) } else { self.height };
+            self.resize(width, height);
+        }
+        self.words[y * self.row_words + x / WORD_BITS] |= 1 << (x % WORD_BITS);
+        true
+    }
+
+    pub fn clear(&mut self, x: IndexType, y: IndexType) {
+        if let Some((id, bit)) = self.index(x, y) {
+            self.words[id] &= !bit;
+        }
+    }
+
+    pub fn clear_all(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    // keeps the dots that still fit
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut tmp = Self::new(width, height);
+        let copy_words = self.row_words.min(tmp.row_words);
+        for y in 0..self.height.min(height) {
+            let from = y * self.row_words;
+            let to = y * tmp.row_words;
+            tmp.words[to..to + copy_words].copy_from_slice(&self.words[from..from + copy_words]);
+        }
+        if width < self.width {
+            tmp.mask_last_words();
+        }
+        *self = tmp;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.height).flat_map(move |y| {
+            let row = &self.words[y * self.row_words..(y + 1) * self.row_words];
+            row.iter().enumerate().flat_map(move |(id, word)| {
+                BitsIter(*word).map(move |bit| Point::new((id * WORD_BITS + bit) as IndexType, y as IndexType))
+            })
+        })
+    }
+
+    /*
+        64 dots of the row y starting at the column x, the dot x is the lowest bit.
+        Everything outside of the matrix reads as empty.
+    */
+    pub fn get_word(&self, x: IndexType, y: IndexType) -> u64 {
+        if y < 0 || y as usize >= self.height || x >= self.width as IndexType {
+            return 0;
+        }
+        let row = &self.words[y as usize * self.row_words..(y as usize + 1) * self.row_words];
+        let read = |id: isize| -> u64 {
+            if id < 0 {
+                0
+            } else {
+                row.get(id as usize).copied().unwrap_or(0)
+            }
+        };
+        let id = (x as isize).div_euclid(WORD_BITS as isize);
+        let shift = (x as isize).rem_euclid(WORD_BITS as isize) as u32;
+        if shift == 0 {
+            read(id)
+        } else {
+            (read(id) >> shift) | (read(id + 1) << (WORD_BITS as u32 - shift))
+        }
+    }
+
+    // whether any dot is set in both, the other matrix is moved by offset
+    pub fn overlaps(&self, other: &BitMatrix, offset: Point) -> bool {
+        let mut found = false;
+        self.for_each_common_word(other, offset, |_, _, word| {
+            found |= word != 0;
+            !found
+        });
+        found
+    }
+
+    // the common dots in the coordinates of self
+    pub fn intersection(&self, other: &BitMatrix, offset: Point) -> Vec<Point> {
+        let mut res = Vec::new();
+        self.for_each_common_word(other, offset, |x, y, word| {
+            res.extend(BitsIter(word).map(|bit| Point::new(x + bit as IndexType, y)));
+            true
+        });
+        res
+    }
+
+    /*
+        Set operations with the other matrix placed at (x, y), a word at a time.
+        blit and toggle grow the matrix to fit the other one.
+    */
+
+    // ORs the other matrix in
+    pub fn blit(&mut self, other: &BitMatrix, x: IndexType, y: IndexType) {
+        if other.is_empty() {
+            return;
+        }
+        self.grow_to_fit(other, x, y);
+        self.combine(other, x, y, false, |a, b| a | b);
+    }
+
+    // clears the dots that are set in the other matrix
+    pub fn subtract(&mut self, other: &BitMatrix, x: IndexType, y: IndexType) {
+        self.combine(other, x, y, false, |a, b| a & !b);
+    }
+
+    // keeps only the dots that are set in the other matrix too
+    pub fn intersect(&mut self, other: &BitMatrix, x: IndexType, y: IndexType) {
+        self.combine(other, x, y, true, |a, b| a & b);
+    }
+
+    // XOR, flips the dots that are set in the other matrix
+    pub fn toggle(&mut self, other: &BitMatrix, x: IndexType, y: IndexType) {
+        self.grow_to_fit(other, x, y);
+        self.combine(other, x, y, false, |a, b| a ^ b);
+    }
+
+    // the part of the matrix from (left, top), outside dots read as empty
+    pub fn crop(&self, left: IndexType, top: IndexType, width: usize, height: usize) -> BitMatrix {
+        let mut res = BitMatrix::new(width, height);
+        for y in 0..height {
+            for id in 0..res.row_words {
+                let word = self.get_word(left + (id * WORD_BITS) as IndexType, top + y as IndexType);
+                res.words[y * res.row_words + id] = word;
+            }
+        }
+        res.mask_last_words();
+        res
+    }
+
+    /*
+        Morphology with a 3x3 square.
+        dilated grows by one dot on every side, the dots move by (1, 1).
+        eroded keeps the size, everything outside is empty.
+    */
+    pub fn dilated(&self) -> BitMatrix {
+        let mut res = BitMatrix::new(self.width + 2, self.height + 2);
+        for y in 0..res.height {
+            for id in 0..res.row_words {
+                let x = (id * WORD_BITS) as IndexType;
+                let mut word = 0;
+                for sy in y as IndexType - 2..=y as IndexType {
+                    word |= self.get_word(x - 2, sy) | self.get_word(x - 1, sy) | self.get_word(x, sy);
+                }
+                res.words[y * res.row_words + id] = word;
+            }
+        }
+        res.mask_last_words();
+        res
+    }
+
+    pub fn eroded(&self) -> BitMatrix {
+        let mut res = BitMatrix::new(self.width, self.height);
+        for y in 0..res.height {
+            for id in 0..res.row_words {
+                let x = (id * WORD_BITS) as IndexType;
+                let mut word = u64::MAX;
+                for sy in y as IndexType - 1..=y as IndexType + 1 {
+                    word &= self.get_word(x - 1, sy) & self.get_word(x, sy) & self.get_word(x + 1, sy);
+                }
+                res.words[y * res.row_words + id] = word;
+            }
+        }
+        res.mask_last_words();
+        res
+    }
+
+    fn grow_to_fit(&mut self, other: &BitMatrix, x: IndexType, y: IndexType) {
+        let right = x + other.width as IndexType - 1;
+        let bottom = y + other.height as IndexType - 1;
+        if right >= 0 && bottom >= 0 && (right as usize >= self.width || bottom as usize >= self.height) {
+            // makes room for the whole source at once
+            self.resize(
+                self.width.max(right as usize + 1),
+                self.height.max(bottom as usize + 1),
+            );
+        }
+    }
+
+    /*
+        Replaces the words of self with op(self, other),
+        only the words under the other matrix unless whole is set.
+    */
+    fn combine<F>(&mut self, other: &BitMatrix, x: IndexType, y: IndexType, whole: bool, op: F)
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        let (rows, first, last) = if whole {
+            (0..self.height, 0, self.row_words as isize - 1)
+        } else {
+            let top = y.max(0) as usize;
+            let bottom = ((y as isize + other.height as isize).max(0) as usize).min(self.height);
+            let left = x.max(0) as isize;
+            let right = (x as isize + other.width as isize - 1).min(self.width as isize - 1);
+            if right < left {
+                return;
+            }
+            (top..bottom.max(top), left / WORD_BITS as isize, right / WORD_BITS as isize)
+        };
+        for ty in rows {
+            for id in first..=last {
+                let src = other.get_word((id as usize * WORD_BITS) as IndexType - x, ty as IndexType - y);
+                let word = &mut self.words[ty * self.row_words + id as usize];
+                *word = op(*word, src);
+            }
+        }
+    }
+
+    // the dots right of the width stay empty
+    fn mask_last_words(&mut self) {
+        if self.row_words == 0 || self.width.is_multiple_of(WORD_BITS) {
+            return;
+        }
+        let mask = (1u64 << (self.width % WORD_BITS)) - 1;
+        for y in 0..self.height {
+            self.words[y * self.row_words + self.row_words - 1] &= mask;
+        }
+    }
+
+    /*
+        Calls back with the AND of both matrices for every 64 dots of the common area,
+        (x, y) is the first dot of the word in the coordinates of self.
+        The callback returns false to stop.
+    */
+    fn for_each_common_word<F>(&self, other: &BitMatrix, offset: Point, mut call: F)
+    where
+        F: FnMut(IndexType, IndexType, u64) -> bool,
+    {
+        let left = offset.x.max(0);
+        let top = offset.y.max(0);
+        let right = (self.width as IndexType).min(offset.x + other.width as IndexType) - 1;
+        let bottom = (self.height as IndexType).min(offset.y + other.height as IndexType) - 1;
+        for y in top..=bottom {
+            let mut x = left;
+            while x <= right {
+                let mut word = self.get_word(x, y) & other.get_word(x - offset.x, y - offset.y);
+                let count = (right - x + 1) as usize;
+                if count < WORD_BITS {
+                    word &= (1u64 << count) - 1;
+                }
+                if word != 0 && !call(x, y, word) {
+                    return;
+                }
+                x += WORD_BITS as IndexType;
+            }
+        }
+    }
+
+    fn index(&self, x: IndexType, y: IndexType) -> Option<(usize, u64)> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            let (x, y) = (x as usize, y as usize);
+            Some((y * self.row_words + x / WORD_BITS, 1 << (x % WORD_BITS)))
+        }
+    }
+}
+
+// positions of the set bits, lowest first
+struct BitsIter(u64);
+
+impl Iterator for BitsIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            let bit = self.0.trailing_zeros() as usize;
+            self.0 &= self.0 - 1;
+            Some(bit)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    type Dots = HashSet<(IndexType, IndexType)>;
+
+    // the widths around the word boundaries
+    const WIDTHS: [usize; 5] = [1, 63, 64, 65, 130];
+    const OFFSETS: [IndexType; 9] = [-130, -64, -63, -1, 0, 5, 64, 65, 130];
+
+    // xorshift, the same dots every run
+    fn random(width: usize, height: usize, seed: u64) -> BitMatrix {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let mut res = BitMatrix::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                if state.is_multiple_of(3) {
+                    res.set(x as IndexType, y as IndexType);
+                }
+            }
+        }
+        res
+    }
+
+    fn full(width: usize, height: usize) -> BitMatrix {
+        let mut res = BitMatrix::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                res.set(x as IndexType, y as IndexType);
+            }
+        }
+        res
+    }
+
+    fn dots(matrix: &BitMatrix) -> Dots {
+        matrix.iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    fn shifted(dots: &Dots, x: IndexType, y: IndexType) -> Dots {
+        dots.iter().map(|(dx, dy)| (dx + x, dy + y)).collect()
+    }
+
+    fn inside(matrix: &BitMatrix, dots: Dots) -> Dots {
+        let (w, h) = (matrix.width as IndexType, matrix.height as IndexType);
+        dots.into_iter().filter(|(x, y)| *x >= 0 && *y >= 0 && *x < w && *y < h).collect()
+    }
+
+    // nothing right of the width, count and iter agree
+    fn check_clean(matrix: &BitMatrix) {
+        assert_eq!(matrix.words.len(), matrix.row_words * matrix.height);
+        assert_eq!(matrix.row_words, matrix.width.div_ceil(WORD_BITS));
+        if !matrix.width.is_multiple_of(WORD_BITS) {
+            let mask = !((1u64 << (matrix.width % WORD_BITS)) - 1);
+            for y in 0..matrix.height {
+                assert_eq!(matrix.words[y * matrix.row_words + matrix.row_words - 1] & mask, 0);
+            }
+        }
+        assert_eq!(matrix.count(), matrix.iter().count());
+        let (width, height) = (matrix.width as IndexType, matrix.height as IndexType);
+        assert!(matrix.iter().all(|p| p.x < width && p.y < height));
+    }
+
+    fn samples() -> Vec<BitMatrix> {
+        let mut res = Vec::new();
+        for (id, width) in WIDTHS.iter().enumerate() {
+            res.push(random(*width, 3, id as u64));
+            res.push(full(*width, 2));
+        }
+        res
+    }
+
+    #[test]
+    fn get_word_reads_any_column() {
+        for matrix in samples() {
+            check_clean(&matrix);
+            for x in -140..(matrix.width as IndexType + 70) {
+                for y in -1..=matrix.height as IndexType {
+                    let word = matrix.get_word(x, y);
+                    for bit in 0..WORD_BITS {
+                        let expected = matrix.get(x + bit as IndexType, y);
+                        assert_eq!(word >> bit & 1 == 1, expected, "x {} y {} bit {}", x, y, bit);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn overlaps_and_intersection_match_the_dots() {
+        for a in samples() {
+            for b in samples() {
+                for ox in OFFSETS {
+                    for oy in [-1, 0, 1] {
+                        let expected: Dots =
+                            dots(&a).intersection(&shifted(&dots(&b), ox, oy)).copied().collect();
+                        let found = a.intersection(&b, Point::new(ox, oy));
+                        assert_eq!(found.len(), expected.len(), "offset {} {}", ox, oy);
+                        assert!(found.iter().all(|p| expected.contains(&(p.x, p.y))));
+                        assert_eq!(a.overlaps(&b, Point::new(ox, oy)), !expected.is_empty());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_operations_match_the_dots() {
+        for a in samples() {
+            for b in samples() {
+                for ox in OFFSETS {
+                    for oy in [-1, 1] {
+                        let (da, db) = (dots(&a), shifted(&dots(&b), ox, oy));
+
+                        let mut blit = a.clone();
+                        blit.blit(&b, ox, oy);
+                        check_clean(&blit);
+                        assert_eq!(dots(&blit), inside(&blit, da.union(&db).copied().collect()));
+
+                        let mut subtract = a.clone();
+                        subtract.subtract(&b, ox, oy);
+                        check_clean(&subtract);
+                        assert_eq!(dots(&subtract), da.difference(&db).copied().collect());
+
+                        let mut intersect = a.clone();
+                        intersect.intersect(&b, ox, oy);
+                        check_clean(&intersect);
+                        assert_eq!(dots(&intersect), da.intersection(&db).copied().collect());
+
+                        let mut toggle = a.clone();
+                        toggle.toggle(&b, ox, oy);
+                        check_clean(&toggle);
+                        let xor = da.symmetric_difference(&db).copied().collect();
+                        assert_eq!(dots(&toggle), inside(&toggle, xor));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blit_grows_to_fit() {
+        let mut a = BitMatrix::new(65, 1);
+        a.blit(&full(65, 2), 64, 1);
+        assert_eq!((a.get_width(), a.get_height()), (129, 3));
+        assert_eq!(a.count(), 130);
+        check_clean(&a);
+    }
+
+    #[test]
+    fn crop_matches_the_dots() {
+        for matrix in samples() {
+            for left in OFFSETS {
+                for width in WIDTHS {
+                    let crop = matrix.crop(left, -1, width, 4);
+                    check_clean(&crop);
+                    let expected = inside(&crop, shifted(&dots(&matrix), -left, 1));
+                    assert_eq!(dots(&crop), expected, "left {} width {}", left, width);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn morphology_matches_the_dots() {
+        for matrix in samples() {
+            let source = dots(&matrix);
+            let around = |x: IndexType, y: IndexType| {
+                (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            };
+
+            let dilated = matrix.dilated();
+            check_clean(&dilated);
+            assert_eq!((dilated.width, dilated.height), (matrix.width + 2, matrix.height + 2));
+            let expected: Dots =
+                source.iter().flat_map(|(x, y)| around(x + 1, y + 1)).collect();
+            assert_eq!(dots(&dilated), expected);
+
+            let eroded = matrix.eroded();
+            check_clean(&eroded);
+            let expected: Dots = source
+                .iter()
+                .copied()
+                .filter(|(x, y)| around(*x, *y).all(|p| source.contains(&p)))
+                .collect();
+            assert_eq!(dots(&eroded), expected);
+        }
+    }
+
+    #[test]
+    fn resize_keeps_the_dots_that_fit() {
+        for matrix in samples() {
+            for width in WIDTHS {
+                for height in [0, 1, 5] {
+                    let mut resized = matrix.clone();
+                    resized.resize(width, height);
+                    check_clean(&resized);
+                    assert_eq!(dots(&resized), inside(&resized, dots(&matrix)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_grows_and_rejects_negatives() {
+        let mut matrix = BitMatrix::new(63, 1);
+        assert!(!matrix.set(-1, 0));
+        assert!(!matrix.set(0, -1));
+        assert!(matrix.set(63, 0));
+        assert!(matrix.set(129, 2));
+        assert!(matrix.get_width() >= 130 && matrix.get_height() >= 3);
+        assert_eq!(dots(&matrix), [(63, 0), (129, 2)].into_iter().collect());
+        check_clean(&matrix);
+    }
+}
@@ -1,6 +1,6 @@
 const MIN: u8 = 0x00;
 const BASE: u32 = 0x2800;
-const DOTS: &'static [u8] = &[0x01, 0x02, 0x04, 0x40, 0x08, 0x10, 0x20, 0x80];
+const DOTS: &[u8] = &[0x01, 0x02, 0x04, 0x40, 0x08, 0x10, 0x20, 0x80];
 
 pub fn empty() -> u8 {
     MIN
@@ -24,6 +24,20 @@ pub fn clean_dot(src: u8, x: usize, y: usize) -> u8 {
     }
 }
 
+pub fn get_dot(src: u8, x: usize, y: usize) -> bool {
+    let id = x * 4 + y;
+    id < DOTS.len() && src & DOTS[id] != 0
+}
+
 pub fn to_char(val: u8) -> char {
     unsafe { char::from_u32_unchecked(val as u32 + BASE) }
 }
+
+pub fn from_char(c: char) -> Option<u8> {
+    let val = c as u32;
+    if (BASE..=BASE + 0xFF).contains(&val) {
+        Some((val - BASE) as u8)
+    } else {
+        None
+    }
+}
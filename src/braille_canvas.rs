@@ -1,23 +1,29 @@
-use crate::{braille, canvas::Canvas, position::IndexType};
+use crate::{
+    braille,
+    canvas::Canvas,
+    color::{CellColor, Color},
+    position::IndexType,
+    terminal_canvas::TerminalCanvas,
+};
 
+// cells are stored row by row in one flat buffer
 pub struct BrailleCanvas {
-    area: Vec<Vec<u8>>,
+    area: Vec<u8>,
+    colors: Vec<CellColor>,
+    width: usize,
+    height: usize,
 }
 
 impl BrailleCanvas {
     pub fn new(x: usize, y: usize) -> Self {
         let x_fix = x / 2 + x % 2;
-        let y_fix = y / 4 + if y % 4 == 0 { 0 } else { 1 };
-        let e_val = braille::empty();
-        let mut canvas = Vec::with_capacity(x_fix);
-        for _ in 0..x_fix {
-            let mut row = Vec::with_capacity(y_fix);
-            for _ in 0..y_fix {
-                row.push(e_val);
-            }
-            canvas.push(row);
+        let y_fix = y / 4 + if y.is_multiple_of(4) { 0 } else { 1 };
+        Self {
+            area: vec![braille::empty(); x_fix * y_fix],
+            colors: vec![CellColor::default(); x_fix * y_fix],
+            width: x_fix,
+            height: y_fix,
         }
-        Self { area: canvas }
     }
 
     fn to_coord(&self, x: usize, y: usize) -> (usize, usize, usize, usize) {
@@ -26,23 +32,37 @@ impl BrailleCanvas {
         (fixed_x, fixed_y, x % 2, y % 4)
     }
 
-    pub fn area(&self) -> &Vec<Vec<u8>> {
+    pub fn area(&self) -> &[u8] {
         &self.area
     }
 
+    pub fn colors(&self) -> &[CellColor] {
+        &self.colors
+    }
+
+    pub fn get_cell(&self, x: usize, y: usize) -> u8 {
+        self.area[y * self.width + x]
+    }
+
+    fn to_cell(&self, x: IndexType, y: IndexType) -> Option<(usize, usize, usize)> {
+        if x >= 0 && y >= 0 {
+            let (fix_x, fix_y, pos_x, pos_y) = self.to_coord(x as usize, y as usize);
+            if fix_x < self.width && fix_y < self.height {
+                return Some((fix_y * self.width + fix_x, pos_x, pos_y));
+            }
+        }
+        None
+    }
+
     fn change_symbol_for(
         &mut self,
         x: IndexType,
         y: IndexType,
         call: fn(u8, usize, usize) -> u8,
     ) -> bool {
-        if x >= 0 && y >= 0 {
-            let (fix_x, fix_y, pos_x, pos_y) = self.to_coord(x as usize, y as usize);
-            if fix_x < self.area.len() && fix_y < self.area[fix_x].len() {
-                let value = self.area[fix_x][fix_y];
-                self.area[fix_x][fix_y] = call(value, pos_x, pos_y);
-                return true;
-            }
+        if let Some((id, pos_x, pos_y)) = self.to_cell(x, y) {
+            self.area[id] = call(self.area[id], pos_x, pos_y);
+            return true;
         }
         false
     }
@@ -56,4 +76,52 @@ impl Canvas for BrailleCanvas {
     fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
         self.change_symbol_for(x, y, braille::clean_dot)
     }
+
+    fn get_dot(&self, x: IndexType, y: IndexType) -> Option<bool> {
+        self.to_cell(x, y)
+            .map(|(id, pos_x, pos_y)| braille::get_dot(self.area[id], pos_x, pos_y))
+    }
+
+    // a braille cell has only one foreground, so the last color drawn wins
+    fn draw_dot_colored(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        if let Some((id, _, _)) = self.to_cell(x, y) {
+            self.colors[id] = self.colors[id].with_fg(color);
+        }
+        self.draw_dot(x, y)
+    }
+
+    fn set_background(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        if let Some((id, _, _)) = self.to_cell(x, y) {
+            self.colors[id] = self.colors[id].with_bg(color);
+            return true;
+        }
+        false
+    }
+}
+
+impl TerminalCanvas for BrailleCanvas {
+    fn new(x: usize, y: usize) -> Self {
+        BrailleCanvas::new(x, y)
+    }
+
+    fn clear(&mut self) {
+        self.area.fill(braille::empty());
+        self.colors.fill(CellColor::default());
+    }
+
+    fn get_cells_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn get_cell_dots(&self) -> (usize, usize) {
+        (2, 4)
+    }
+
+    fn get_cell_char(&self, x: usize, y: usize) -> char {
+        braille::to_char(self.get_cell(x, y))
+    }
+
+    fn get_cell_color(&self, x: usize, y: usize) -> CellColor {
+        self.colors[y * self.width + x]
+    }
 }
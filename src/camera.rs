@@ -0,0 +1,202 @@
+use std::cmp::{max, min};
+
+use crate::{
+    canvas::Canvas,
+    color::Color,
+    drawable::Drawable,
+    game_object::GameObject,
+    position::{AsPoint, IndexType, Point},
+};
+
+/*
+    a window of `view` size over a world of `world` size.
+    `pos` is the world point shown at the top left corner of the canvas.
+*/
+pub struct Camera {
+    pos: Point,
+    view: Point,
+    world: Point,
+    dead_zone: Point,
+}
+
+impl Camera {
+    pub fn new(
+        view_width: IndexType,
+        view_height: IndexType,
+        world_width: IndexType,
+        world_height: IndexType,
+    ) -> Self {
+        Self {
+            pos: Point::new(0, 0),
+            view: Point::new(view_width, view_height),
+            world: Point::new(world_width, world_height),
+            dead_zone: Point::new(0, 0),
+        }
+    }
+
+    // the target can get that close to the view edges before the camera moves
+    pub fn with_dead_zone(mut self, margin_x: IndexType, margin_y: IndexType) -> Self {
+        self.dead_zone = Point::new(max(margin_x, 0), max(margin_y, 0));
+        self
+    }
+
+    pub fn get_pos(&self) -> Point {
+        self.pos
+    }
+
+    pub fn get_view_size(&self) -> Point {
+        self.view
+    }
+
+    pub fn get_world_size(&self) -> Point {
+        self.world
+    }
+
+    pub fn set_view_size(&mut self, width: IndexType, height: IndexType) {
+        self.view = Point::new(width, height);
+        self.move_to(self.pos.x, self.pos.y);
+    }
+
+    pub fn move_to(&mut self, x: IndexType, y: IndexType) {
+        let max_x = max(self.world.x - self.view.x, 0);
+        let max_y = max(self.world.y - self.view.y, 0);
+        self.pos.move_to(x.clamp(0, max_x), y.clamp(0, max_y));
+    }
+
+    pub fn center_on(&mut self, p: Point) {
+        self.move_to(p.x - self.view.x / 2, p.y - self.view.y / 2);
+    }
+
+    // rect is (top left, bottom right) in world coordinates, as `GameObject::get_rect` gives
+    pub fn follow(&mut self, rect: (Point, Point)) {
+        let margin_x = min(self.dead_zone.x, (self.view.x - 1) / 2);
+        let margin_y = min(self.dead_zone.y, (self.view.y - 1) / 2);
+        let mut pos = self.pos;
+        if rect.0.x < pos.x + margin_x {
+            pos.x = rect.0.x - margin_x;
+        } else if rect.1.x > pos.x + self.view.x - 1 - margin_x {
+            pos.x = rect.1.x - (self.view.x - 1 - margin_x);
+        }
+        if rect.0.y < pos.y + margin_y {
+            pos.y = rect.0.y - margin_y;
+        } else if rect.1.y > pos.y + self.view.y - 1 - margin_y {
+            pos.y = rect.1.y - (self.view.y - 1 - margin_y);
+        }
+        self.move_to(pos.x, pos.y);
+    }
+
+    pub fn is_visible(&self, rect: (Point, Point)) -> bool {
+        rect.1.x >= self.pos.x
+            && rect.1.y >= self.pos.y
+            && rect.0.x < self.pos.x + self.view.x
+            && rect.0.y < self.pos.y + self.view.y
+    }
+
+    pub fn to_view(&self, p: Point) -> Point {
+        (p.x - self.pos.x, p.y - self.pos.y).as_point()
+    }
+
+    pub fn to_world(&self, p: Point) -> Point {
+        (p.x + self.pos.x, p.y + self.pos.y).as_point()
+    }
+
+    pub fn view<'a>(&self, canvas: &'a mut dyn Canvas) -> CameraView<'a> {
+        CameraView {
+            canvas,
+            offset: self.pos,
+        }
+    }
+
+    pub fn draw(&self, object: &dyn Drawable, canvas: &mut dyn Canvas) {
+        object.draw(&mut self.view(canvas));
+    }
+
+    // same as `draw` but skips objects that are out of the view
+    pub fn draw_object<T: Drawable + GameObject>(&self, object: &T, canvas: &mut dyn Canvas) {
+        if self.is_visible(object.get_rect()) {
+            self.draw(object, canvas);
+        }
+    }
+}
+
+// a canvas that takes world coordinates and draws them shifted by the camera position
+pub struct CameraView<'a> {
+    canvas: &'a mut dyn Canvas,
+    offset: Point,
+}
+
+impl Canvas for CameraView<'_> {
+    fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.canvas.draw_dot(x - self.offset.x, y - self.offset.y)
+    }
+
+    fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.canvas.clean_dot(x - self.offset.x, y - self.offset.y)
+    }
+
+    fn draw_dot_colored(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        self.canvas
+            .draw_dot_colored(x - self.offset.x, y - self.offset.y, color)
+    }
+
+    fn set_background(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        self.canvas
+            .set_background(x - self.offset.x, y - self.offset.y, color)
+    }
+
+    fn get_dot(&self, x: IndexType, y: IndexType) -> Option<bool> {
+        self.canvas.get_dot(x - self.offset.x, y - self.offset.y)
+    }
+
+    fn cover_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.canvas.cover_dot(x - self.offset.x, y - self.offset.y)
+    }
+}
+
+// a canvas that takes view coordinates and draws them `scale` times smaller
+pub struct ScaledView<'a> {
+    canvas: &'a mut dyn Canvas,
+    scale: IndexType,
+}
+
+impl<'a> ScaledView<'a> {
+    pub fn new(canvas: &'a mut dyn Canvas, scale: usize) -> Self {
+        Self {
+            canvas,
+            scale: scale.clamp(1, IndexType::MAX as usize) as IndexType,
+        }
+    }
+}
+
+// several view dots fall on one canvas dot, any set one sets it
+impl Canvas for ScaledView<'_> {
+    fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.canvas
+            .draw_dot(x.div_euclid(self.scale), y.div_euclid(self.scale))
+    }
+
+    fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.canvas
+            .clean_dot(x.div_euclid(self.scale), y.div_euclid(self.scale))
+    }
+
+    fn draw_dot_colored(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        self.canvas
+            .draw_dot_colored(x.div_euclid(self.scale), y.div_euclid(self.scale), color)
+    }
+
+    fn set_background(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        self.canvas
+            .set_background(x.div_euclid(self.scale), y.div_euclid(self.scale), color)
+    }
+
+    fn get_dot(&self, x: IndexType, y: IndexType) -> Option<bool> {
+        self.canvas
+            .get_dot(x.div_euclid(self.scale), y.div_euclid(self.scale))
+    }
+
+    fn cover_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.canvas
+            .cover_dot(x.div_euclid(self.scale), y.div_euclid(self.scale))
+    }
+}
@@ -1,43 +1,49 @@
 use std::cmp::{max, min};
 
-use crate::position::{AsPoint, IndexType, Point};
+use crate::{
+    color::Color,
+    font::{span, TextAlign, TextStyle},
+    line::{self, Line, LineStyle},
+    position::{AsPoint, IndexType, Point},
+};
 
 pub trait Canvas {
     fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool;
     fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool;
 
+    // canvases without a color layer just plot the dot
+    fn draw_dot_colored(&mut self, x: IndexType, y: IndexType, _color: Color) -> bool {
+        self.draw_dot(x, y)
+    }
+
+    fn set_background(&mut self, _x: IndexType, _y: IndexType, _color: Color) -> bool {
+        false
+    }
+
+    // hides the dot of the layers below, only layered canvases care about it
+    fn cover_dot(&mut self, _x: IndexType, _y: IndexType) -> bool {
+        false
+    }
+
+    // Some(is_set) for a dot inside the canvas, None if outside or if the canvas can't tell
+    fn get_dot(&self, _x: IndexType, _y: IndexType) -> Option<bool> {
+        None
+    }
+
     fn draw_line(&mut self, from: Point, to: Point) {
-        let dx: i32 = to.x as i32 - from.x as i32;
-        let dy: i32 = to.y as i32 - from.y as i32;
-        if dx > 0 || dy > 0 {
-            if dx.abs() > dy.abs() {
-                let mut y: f32 = from.y as f32 + 0.5;
-                let dly = dy as f32 / dx as f32;
-                if dx > 0 {
-                    for ix in from.x..=to.x {
-                        self.draw_dot(ix, y.floor() as IndexType);
-                        y = y + dly;
-                    }
-                } else {
-                    for ix in (from.x..=to.x).rev() {
-                        self.draw_dot(ix, y.floor() as IndexType);
-                        y = y - dly;
-                    }
-                }
-            } else {
-                // dy >= dx
-                let mut x: f32 = from.x as f32 + 0.5;
-                let dlx = dx as f32 / dy as f32;
-                if dy > 0 {
-                    for iy in from.y..=to.y {
-                        self.draw_dot(x.floor() as IndexType, iy);
-                        x = x + dlx;
-                    }
-                } else {
-                    for iy in (from.y..=to.y).rev() {
-                        self.draw_dot(x.floor() as IndexType, iy);
-                        x = x - dlx;
-                    }
+        for p in Line::new(from, to) {
+            self.draw_dot(p.x, p.y);
+        }
+    }
+
+    fn draw_line_styled(&mut self, from: Point, to: Point, style: &LineStyle) {
+        let line = Line::new(from, to);
+        let x_major = line.is_x_major();
+        for (step, p) in line.enumerate() {
+            if style.is_on(step) {
+                for offset in line::stroke_offsets(style.get_width()) {
+                    let sp = line::stroke_point(p, offset, x_major);
+                    self.draw_dot(sp.x, sp.y);
                 }
             }
         }
@@ -77,6 +83,212 @@ pub trait Canvas {
         }
     }
 
+    fn fill_rectangle(&mut self, from: Point, to: Point) {
+        let (min_x, min_y) = (min(from.x, to.x), min(from.y, to.y));
+        let (max_x, max_y) = (max(from.x, to.x), max(from.y, to.y));
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.draw_dot(x, y);
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, center: Point, radius: IndexType) {
+        self.fill_ellipse(center, radius, radius);
+    }
+
+    fn draw_ellipse(&mut self, center: Point, radius_x: IndexType, radius_y: IndexType) {
+        let (rx, ry) = (radius_x as f32, radius_y as f32);
+        if radius_x <= 0 || radius_y <= 0 {
+            self.draw_line(
+                (center.x - radius_x, center.y - radius_y).as_point(),
+                (center.x + radius_x, center.y + radius_y).as_point(),
+            );
+            return;
+        }
+        // walk both axes so the steep parts of the curve have no gaps
+        for x in 0..=radius_x {
+            let fx = x as f32 / rx;
+            let y = (ry * (1.0 - fx * fx).max(0.0).sqrt()).round() as IndexType;
+            self.draw_dot(center.x + x, center.y + y);
+            self.draw_dot(center.x - x, center.y + y);
+            self.draw_dot(center.x + x, center.y - y);
+            self.draw_dot(center.x - x, center.y - y);
+        }
+        for y in 0..=radius_y {
+            let fy = y as f32 / ry;
+            let x = (rx * (1.0 - fy * fy).max(0.0).sqrt()).round() as IndexType;
+            self.draw_dot(center.x + x, center.y + y);
+            self.draw_dot(center.x - x, center.y + y);
+            self.draw_dot(center.x + x, center.y - y);
+            self.draw_dot(center.x - x, center.y - y);
+        }
+    }
+
+    fn fill_ellipse(&mut self, center: Point, radius_x: IndexType, radius_y: IndexType) {
+        let (rx, ry) = (radius_x as f32 + 0.5, radius_y as f32 + 0.5);
+        for y in -radius_y..=radius_y {
+            let fy = y as f32 / ry;
+            let half = (rx * (1.0 - fy * fy).max(0.0).sqrt()).floor() as IndexType;
+            let half = min(half, radius_x);
+            for x in -half..=half {
+                self.draw_dot(center.x + x, center.y + y);
+            }
+        }
+    }
+
+    /*
+        angles are in degrees, 0 points to +x and they grow clockwise
+        (y goes down on the screen)
+    */
+    fn draw_arc(&mut self, center: Point, radius: IndexType, start_angle: f32, end_angle: f32) {
+        if radius <= 0 {
+            self.draw_dot(center.x, center.y);
+            return;
+        }
+        let (from, to) = if start_angle <= end_angle {
+            (start_angle, end_angle)
+        } else {
+            (end_angle, start_angle)
+        };
+        // NaN or infinite angles have nothing to draw
+        let span = to - from;
+        if !span.is_finite() {
+            return;
+        }
+        // more than a full turn draws the same dots again
+        let span = span.min(360.0) as f64;
+        // half a dot along the curve per step, the count does not depend on the angle size
+        let step = (0.5 / radius as f64).to_degrees();
+        let steps = (span / step).ceil() as usize;
+        // big angles lose the small steps, the start is moved to the first turn
+        let start = (from as f64).rem_euclid(360.0);
+        for i in 0..=steps {
+            let angle = if steps == 0 {
+                start
+            } else {
+                start + span * i as f64 / steps as f64
+            };
+            let a = angle.to_radians();
+            let x = (radius as f64 * a.cos()).round() as IndexType;
+            let y = (radius as f64 * a.sin()).round() as IndexType;
+            self.draw_dot(center.x + x, center.y + y);
+        }
+    }
+
+    fn draw_polygon(&mut self, points: &[Point]) {
+        match points.len() {
+            0 => {}
+            1 => {
+                self.draw_dot(points[0].x, points[0].y);
+            }
+            len => {
+                for i in 0..len {
+                    self.draw_line(points[i], points[(i + 1) % len]);
+                }
+            }
+        }
+    }
+
+    // even-odd scanline fill, works for concave and self-intersecting polygons
+    fn fill_polygon(&mut self, points: &[Point]) {
+        if points.len() < 3 {
+            self.draw_polygon(points);
+            return;
+        }
+        let min_y = points.iter().map(|p| p.y).min().unwrap_or(0);
+        let max_y = points.iter().map(|p| p.y).max().unwrap_or(0);
+        let mut crossings: Vec<f32> = Vec::new();
+        for y in min_y..=max_y {
+            let scan = y as f32 + 0.5;
+            crossings.clear();
+            for i in 0..points.len() {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                let (ay, by) = (a.y as f32, b.y as f32);
+                if (ay <= scan && by > scan) || (by <= scan && ay > scan) {
+                    let t = (scan - ay) / (by - ay);
+                    crossings.push(a.x as f32 + t * (b.x as f32 - a.x as f32));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            for pair in crossings.chunks_exact(2) {
+                let from = pair[0].round() as IndexType;
+                let to = pair[1].round() as IndexType;
+                for x in from..=to {
+                    self.draw_dot(x, y);
+                }
+            }
+        }
+        self.draw_polygon(points);
+    }
+
+    // 4-connected fill of the empty area around `start`; needs `get_dot`
+    fn flood_fill(&mut self, start: Point) {
+        let mut stack = vec![start];
+        while let Some(p) = stack.pop() {
+            if self.get_dot(p.x, p.y) != Some(false) {
+                continue;
+            }
+            self.draw_dot(p.x, p.y);
+            stack.push((p.x + 1, p.y).as_point());
+            stack.push((p.x - 1, p.y).as_point());
+            stack.push((p.x, p.y + 1).as_point());
+            stack.push((p.x, p.y - 1).as_point());
+        }
+    }
+
+    /*
+        `pos` is the top of the text; depending on the alignment
+        it's the left edge, the center or the right edge of every line.
+        '\n' starts a new line.
+        The places are counted in i64, the text stops where IndexType ends.
+    */
+    fn draw_text(&mut self, pos: Point, text: &str, style: &TextStyle) {
+        let font = style.get_font();
+        let (width, height) = (font.get_width() as i64, font.get_height() as i64);
+        let (first, last) = (IndexType::MIN as i64, IndexType::MAX as i64);
+        for (line_id, line) in text.lines().enumerate() {
+            let top = pos.y as i64 + line_id as i64 * (height + 1);
+            if top > last {
+                break;
+            }
+            if top + height <= first {
+                continue;
+            }
+            let line_width = span(line.chars().count(), font.get_width());
+            let left = match style.get_align() {
+                TextAlign::Left => pos.x as i64,
+                TextAlign::Center => pos.x as i64 - line_width / 2,
+                TextAlign::Right => pos.x as i64 - line_width + 1,
+            };
+            for (char_id, c) in line.chars().enumerate() {
+                let char_left = left + char_id as i64 * (width + 1);
+                if char_left > last {
+                    break;
+                }
+                if char_left + width <= first {
+                    continue;
+                }
+                for y in 0..font.get_height() {
+                    for x in 0..font.get_width() {
+                        let (dx, dy) = (char_left + x as i64, top + y as i64);
+                        if !(first..=last).contains(&dx) || !(first..=last).contains(&dy) {
+                            continue;
+                        }
+                        let (dx, dy) = (dx as IndexType, dy as IndexType);
+                        if !font.is_dot(c, x, y) || style.is_clipped(dx, dy) {
+                            continue;
+                        }
+                        match style.get_color() {
+                            Some(color) => self.draw_dot_colored(dx, dy, color),
+                            None => self.draw_dot(dx, dy),
+                        };
+                    }
+                }
+            }
+        }
+    }
+
     /*
         ' ' - increments X
         '\r' - ignored
@@ -125,3 +337,68 @@ pub trait Canvas {
         &value[id + 1..value.len()]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless_canvas::HeadlessCanvas;
+
+    fn count_dots(canvas: &HeadlessCanvas) -> usize {
+        let mut count = 0;
+        for y in 0..canvas.get_height() {
+            for x in 0..canvas.get_width() {
+                if canvas.get_dot(x as IndexType, y as IndexType) == Some(true) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn arc_with_nan_or_infinite_angles_draws_nothing() {
+        let mut canvas = HeadlessCanvas::new(21, 21);
+        canvas.draw_arc((10, 10).as_point(), 8, f32::NAN, 90.0);
+        canvas.draw_arc((10, 10).as_point(), 8, 0.0, f32::INFINITY);
+        assert_eq!(count_dots(&canvas), 0);
+    }
+
+    #[test]
+    fn arc_with_huge_angles_ends() {
+        let mut canvas = HeadlessCanvas::new(21, 21);
+        canvas.draw_arc((10, 10).as_point(), 8, 1.0e9, 1.0e9 + 90.0);
+        assert!(count_dots(&canvas) > 0);
+
+        let mut full = HeadlessCanvas::new(21, 21);
+        full.draw_arc((10, 10).as_point(), 8, 0.0, 360.0);
+        let mut many_turns = HeadlessCanvas::new(21, 21);
+        many_turns.draw_arc((10, 10).as_point(), 8, -1.0e30, 1.0e30);
+        // the same circle from another start, only the rounding differs
+        assert!(count_dots(&full).abs_diff(count_dots(&many_turns)) <= 2);
+    }
+
+    #[test]
+    fn long_texts_do_not_overflow() {
+        use crate::font::FONT_5X7;
+
+        let long = "W".repeat(6000);
+        assert_eq!(FONT_5X7.text_width(&long), IndexType::MAX);
+        assert_eq!(FONT_5X7.text_height(&"W\n".repeat(5000)), IndexType::MAX);
+        assert_eq!(FONT_5X7.text_width("WW"), 11);
+
+        let draw = |text: &str, align: TextAlign| {
+            let mut canvas = HeadlessCanvas::new(40, 20);
+            let style = TextStyle::new(&FONT_5X7).with_align(align);
+            canvas.draw_text((1, 1).as_point(), text, &style);
+            canvas
+        };
+        let count = |text: &str, align| count_dots(&draw(text, align));
+        // only the first chars are on the canvas
+        assert_eq!(count(&long, TextAlign::Left), count("WWWWWWW", TextAlign::Left));
+        assert_eq!(count(&long, TextAlign::Right), count("W", TextAlign::Right));
+        // an even count of chars centers on the same columns
+        assert_eq!(count(&long, TextAlign::Center), count(&long[..14], TextAlign::Center));
+        let lines = "W\n".repeat(6000);
+        assert_eq!(count(&lines, TextAlign::Left), count("W\nW\nW", TextAlign::Left));
+    }
+}
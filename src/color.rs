@@ -0,0 +1,89 @@
+pub use crossterm::style::Color;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct CellColor {
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl CellColor {
+    pub fn new(fg: Color, bg: Color) -> Self {
+        Self { fg, bg }
+    }
+
+    pub fn with_fg(&self, fg: Color) -> Self {
+        Self { fg, bg: self.bg }
+    }
+
+    pub fn with_bg(&self, bg: Color) -> Self {
+        Self { fg: self.fg, bg }
+    }
+}
+
+impl Default for CellColor {
+    fn default() -> Self {
+        Self {
+            fg: Color::Reset,
+            bg: Color::Black,
+        }
+    }
+}
+
+const ANSI_COLORS: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [128, 0, 0],
+    [0, 128, 0],
+    [128, 128, 0],
+    [0, 0, 128],
+    [128, 0, 128],
+    [0, 128, 128],
+    [192, 192, 192],
+    [128, 128, 128],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [0, 0, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+// approximate RGB value of a terminal color, `reset` is used for Color::Reset
+pub fn to_rgb(color: Color, reset: [u8; 3]) -> [u8; 3] {
+    match color {
+        Color::Reset => reset,
+        Color::Black => ANSI_COLORS[0],
+        Color::DarkRed => ANSI_COLORS[1],
+        Color::DarkGreen => ANSI_COLORS[2],
+        Color::DarkYellow => ANSI_COLORS[3],
+        Color::DarkBlue => ANSI_COLORS[4],
+        Color::DarkMagenta => ANSI_COLORS[5],
+        Color::DarkCyan => ANSI_COLORS[6],
+        Color::Grey => ANSI_COLORS[7],
+        Color::DarkGrey => ANSI_COLORS[8],
+        Color::Red => ANSI_COLORS[9],
+        Color::Green => ANSI_COLORS[10],
+        Color::Yellow => ANSI_COLORS[11],
+        Color::Blue => ANSI_COLORS[12],
+        Color::Magenta => ANSI_COLORS[13],
+        Color::Cyan => ANSI_COLORS[14],
+        Color::White => ANSI_COLORS[15],
+        Color::Rgb { r, g, b } => [r, g, b],
+        Color::AnsiValue(v) => ansi_value_to_rgb(v),
+    }
+}
+
+fn ansi_value_to_rgb(value: u8) -> [u8; 3] {
+    match value {
+        0..=15 => ANSI_COLORS[value as usize],
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let v = value - 16;
+            [level(v / 36), level((v / 6) % 6), level(v % 6)]
+        }
+        _ => {
+            let gray = 8 + (value - 232) * 10;
+            [gray, gray, gray]
+        }
+    }
+}
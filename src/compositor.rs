@@ -0,0 +1,146 @@
+use crate::{
+    canvas::Canvas,
+    color::Color,
+    drawable::Drawable,
+    layer::Layer,
+    position::IndexType,
+};
+
+#[derive(Copy, Clone, Default)]
+struct Cell {
+    dot: bool,
+    cover: bool,
+    color: Option<Color>,
+    background: Option<Color>,
+}
+
+/*
+    one layer of the compositor.
+    besides dots it keeps a cover mask: covered dots hide everything
+    in the layers below, even where this layer has no dot of its own.
+*/
+pub struct LayerCanvas {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl LayerCanvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width * height],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+
+    fn to_id(&self, x: IndexType, y: IndexType) -> Option<usize> {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            Some(y as usize * self.width + x as usize)
+        } else {
+            None
+        }
+    }
+
+    fn change_cell_for<F: FnOnce(&mut Cell)>(&mut self, x: IndexType, y: IndexType, call: F) -> bool {
+        if let Some(id) = self.to_id(x, y) {
+            call(&mut self.cells[id]);
+            return true;
+        }
+        false
+    }
+}
+
+impl Canvas for LayerCanvas {
+    fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.change_cell_for(x, y, |c| c.dot = true)
+    }
+
+    fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.change_cell_for(x, y, |c| c.dot = false)
+    }
+
+    fn draw_dot_colored(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        self.change_cell_for(x, y, |c| {
+            c.dot = true;
+            c.color = Some(color);
+        })
+    }
+
+    fn set_background(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        self.change_cell_for(x, y, |c| c.background = Some(color))
+    }
+
+    fn get_dot(&self, x: IndexType, y: IndexType) -> Option<bool> {
+        self.to_id(x, y).map(|id| self.cells[id].dot)
+    }
+
+    fn cover_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        self.change_cell_for(x, y, |c| c.cover = true)
+    }
+}
+
+pub struct Compositor {
+    layers: Vec<LayerCanvas>,
+    width: usize,
+    height: usize,
+}
+
+impl Compositor {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            layers: Layer::ALL
+                .iter()
+                .map(|_| LayerCanvas::new(width, height))
+                .collect(),
+            width,
+            height,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for l in self.layers.iter_mut() {
+            l.clear();
+        }
+    }
+
+    pub fn get_layer_mut(&mut self, layer: Layer) -> &mut LayerCanvas {
+        &mut self.layers[layer.index()]
+    }
+
+    // draws the object into the layer it asks for
+    pub fn add(&mut self, object: &dyn Drawable) {
+        object.draw(self.get_layer_mut(object.get_layer()));
+    }
+
+    // merges the layers top to bottom: the first dot or cover found wins
+    pub fn compose(&self, canvas: &mut dyn Canvas) {
+        for id in 0..self.width * self.height {
+            let (x, y) = ((id % self.width) as IndexType, (id / self.width) as IndexType);
+            let mut background = None;
+            for layer in self.layers.iter().rev() {
+                let cell = &layer.cells[id];
+                if background.is_none() {
+                    background = cell.background;
+                }
+                if cell.dot {
+                    match cell.color {
+                        Some(color) => canvas.draw_dot_colored(x, y, color),
+                        None => canvas.draw_dot(x, y),
+                    };
+                    break;
+                }
+                if cell.cover {
+                    break;
+                }
+            }
+            if let Some(color) = background {
+                canvas.set_background(x, y, color);
+            }
+        }
+    }
+}
@@ -30,5 +30,19 @@ impl Direction {
             Direction::Left => Direction::Up,
         }
     }
-}
 
+    // clockwise quarter turns from Up
+    pub fn get_quarter_turns(&self) -> i32 {
+        match *self {
+            Direction::Up => 0,
+            Direction::Right => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+        }
+    }
+
+    // clockwise quarter turns needed to face the other direction
+    pub fn quarter_turns_to(&self, other: Direction) -> i32 {
+        (other.get_quarter_turns() - self.get_quarter_turns()).rem_euclid(4)
+    }
+}
@@ -1,5 +1,9 @@
-use crate::canvas::Canvas;
+use crate::{canvas::Canvas, layer::Layer};
 
 pub trait Drawable {
     fn draw(&self, canvas: &mut dyn Canvas);
+
+    fn get_layer(&self) -> Layer {
+        Layer::Objects
+    }
 }
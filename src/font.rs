@@ -0,0 +1,560 @@
+use crate::{
+    color::Color,
+    position::{IndexType, Point},
+};
+
+/*
+    fixed size bitmap font, one byte per glyph row,
+    the highest of the `width` bits is the leftmost dot.
+    lowercase letters are drawn as uppercase ones, unknown chars as '?'.
+*/
+pub struct Font {
+    width: IndexType,
+    height: IndexType,
+    glyphs: &'static [(char, &'static [u8])],
+}
+
+pub const FONT_3X5: Font = Font {
+    width: 3,
+    height: 5,
+    glyphs: GLYPHS_3X5,
+};
+
+pub const FONT_5X7: Font = Font {
+    width: 5,
+    height: 7,
+    glyphs: GLYPHS_5X7,
+};
+
+impl Font {
+    pub fn get_width(&self) -> IndexType {
+        self.width
+    }
+
+    pub fn get_height(&self) -> IndexType {
+        self.height
+    }
+
+    fn find(&self, c: char) -> Option<&'static [u8]> {
+        self.glyphs.iter().find(|g| g.0 == c).map(|g| g.1)
+    }
+
+    pub fn get_glyph(&self, c: char) -> &'static [u8] {
+        self.find(c)
+            .or_else(|| self.find(c.to_ascii_uppercase()))
+            .or_else(|| self.find('?'))
+            .unwrap_or(&[])
+    }
+
+    pub fn is_dot(&self, c: char, x: IndexType, y: IndexType) -> bool {
+        if x < 0 || y < 0 || x >= self.width {
+            return false;
+        }
+        match self.get_glyph(c).get(y as usize) {
+            Some(row) => row & (1 << (self.width - 1 - x)) != 0,
+            None => false,
+        }
+    }
+
+    // one empty column between chars, IndexType::MAX for longer texts
+    pub fn text_width(&self, line: &str) -> IndexType {
+        saturate(span(line.chars().count(), self.width))
+    }
+
+    // one empty row between lines, IndexType::MAX for longer texts
+    pub fn text_height(&self, text: &str) -> IndexType {
+        saturate(span(text.lines().count(), self.height))
+    }
+}
+
+// `count` cells of `size` dots with a gap of one between them
+pub fn span(count: usize, size: IndexType) -> i64 {
+    if count == 0 {
+        0
+    } else {
+        (count as i64).saturating_mul(size as i64 + 1) - 1
+    }
+}
+
+fn saturate(value: i64) -> IndexType {
+    value.clamp(IndexType::MIN as i64, IndexType::MAX as i64) as IndexType
+}
+
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+pub struct TextStyle {
+    font: &'static Font,
+    align: TextAlign,
+    clip: Option<(Point, Point)>,
+    color: Option<Color>,
+}
+
+impl TextStyle {
+    pub fn new(font: &'static Font) -> Self {
+        Self {
+            font,
+            align: TextAlign::Left,
+            clip: None,
+            color: None,
+        }
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    // dots outside of the (top left, bottom right) rect, both included, are skipped
+    pub fn with_clip(mut self, from: Point, to: Point) -> Self {
+        self.clip = Some((from, to));
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn get_font(&self) -> &'static Font {
+        self.font
+    }
+
+    pub fn get_align(&self) -> TextAlign {
+        self.align
+    }
+
+    pub fn get_color(&self) -> Option<Color> {
+        self.color
+    }
+
+    pub fn is_clipped(&self, x: IndexType, y: IndexType) -> bool {
+        match self.clip {
+            Some((from, to)) => x < from.x || y < from.y || x > to.x || y > to.y,
+            None => false,
+        }
+    }
+}
+
+const GLYPHS_3X5: &[(char, &[u8])] = &[
+    (' ', &[0b000, 0b000, 0b000, 0b000, 0b000]),
+    ('!', &[0b010, 0b010, 0b010, 0b000, 0b010]),
+    ('"', &[0b101, 0b101, 0b000, 0b000, 0b000]),
+    ('#', &[0b101, 0b111, 0b101, 0b111, 0b101]),
+    ('%', &[0b101, 0b001, 0b010, 0b100, 0b101]),
+    ('\'', &[0b010, 0b010, 0b000, 0b000, 0b000]),
+    ('(', &[0b001, 0b010, 0b010, 0b010, 0b001]),
+    (')', &[0b100, 0b010, 0b010, 0b010, 0b100]),
+    ('*', &[0b000, 0b101, 0b010, 0b101, 0b000]),
+    ('+', &[0b000, 0b010, 0b111, 0b010, 0b000]),
+    (',', &[0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('-', &[0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('.', &[0b000, 0b000, 0b000, 0b000, 0b010]),
+    ('/', &[0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('0', &[0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', &[0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', &[0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', &[0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', &[0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', &[0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', &[0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', &[0b111, 0b001, 0b001, 0b010, 0b010]),
+    ('8', &[0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', &[0b111, 0b101, 0b111, 0b001, 0b111]),
+    (':', &[0b000, 0b010, 0b000, 0b010, 0b000]),
+    (';', &[0b000, 0b010, 0b000, 0b010, 0b100]),
+    ('<', &[0b001, 0b010, 0b100, 0b010, 0b001]),
+    ('=', &[0b000, 0b111, 0b000, 0b111, 0b000]),
+    ('>', &[0b100, 0b010, 0b001, 0b010, 0b100]),
+    ('?', &[0b111, 0b001, 0b010, 0b000, 0b010]),
+    ('A', &[0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', &[0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', &[0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', &[0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', &[0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', &[0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', &[0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', &[0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', &[0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', &[0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', &[0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', &[0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', &[0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', &[0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', &[0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', &[0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', &[0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', &[0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', &[0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', &[0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', &[0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', &[0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', &[0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', &[0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', &[0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', &[0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('[', &[0b011, 0b010, 0b010, 0b010, 0b011]),
+    (']', &[0b110, 0b010, 0b010, 0b010, 0b110]),
+    ('_', &[0b000, 0b000, 0b000, 0b000, 0b111]),
+];
+
+const GLYPHS_5X7: &[(char, &[u8])] = &[
+    (
+        ' ',
+        &[
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '!',
+        &[
+            0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        '"',
+        &[
+            0b01010, 0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '#',
+        &[
+            0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010,
+        ],
+    ),
+    (
+        '%',
+        &[
+            0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011,
+        ],
+    ),
+    (
+        '\'',
+        &[
+            0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '(',
+        &[
+            0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010,
+        ],
+    ),
+    (
+        ')',
+        &[
+            0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '*',
+        &[
+            0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000,
+        ],
+    ),
+    (
+        '+',
+        &[
+            0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000,
+        ],
+    ),
+    (
+        ',',
+        &[
+            0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '-',
+        &[
+            0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '.',
+        &[
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100,
+        ],
+    ),
+    (
+        '/',
+        &[
+            0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000,
+        ],
+    ),
+    (
+        '0',
+        &[
+            0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '1',
+        &[
+            0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        '2',
+        &[
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111,
+        ],
+    ),
+    (
+        '3',
+        &[
+            0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '4',
+        &[
+            0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010,
+        ],
+    ),
+    (
+        '5',
+        &[
+            0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '6',
+        &[
+            0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '7',
+        &[
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000,
+        ],
+    ),
+    (
+        '8',
+        &[
+            0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '9',
+        &[
+            0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100,
+        ],
+    ),
+    (
+        ':',
+        &[
+            0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000,
+        ],
+    ),
+    (
+        ';',
+        &[
+            0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '<',
+        &[
+            0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010,
+        ],
+    ),
+    (
+        '=',
+        &[
+            0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '>',
+        &[
+            0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '?',
+        &[
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        'A',
+        &[
+            0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'B',
+        &[
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110,
+        ],
+    ),
+    (
+        'C',
+        &[
+            0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'D',
+        &[
+            0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100,
+        ],
+    ),
+    (
+        'E',
+        &[
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'F',
+        &[
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'G',
+        &[
+            0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111,
+        ],
+    ),
+    (
+        'H',
+        &[
+            0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'I',
+        &[
+            0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        'J',
+        &[
+            0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100,
+        ],
+    ),
+    (
+        'K',
+        &[
+            0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'L',
+        &[
+            0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'M',
+        &[
+            0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'N',
+        &[
+            0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'O',
+        &[
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'P',
+        &[
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'Q',
+        &[
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101,
+        ],
+    ),
+    (
+        'R',
+        &[
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'S',
+        &[
+            0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110,
+        ],
+    ),
+    (
+        'T',
+        &[
+            0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'U',
+        &[
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'V',
+        &[
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+        ],
+    ),
+    (
+        'W',
+        &[
+            0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010,
+        ],
+    ),
+    (
+        'X',
+        &[
+            0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'Y',
+        &[
+            0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'Z',
+        &[
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        '[',
+        &[
+            0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110,
+        ],
+    ),
+    (
+        ']',
+        &[
+            0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110,
+        ],
+    ),
+    (
+        '_',
+        &[
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111,
+        ],
+    ),
+];
@@ -0,0 +1,89 @@
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError, TrySendError};
+
+/*
+    bounded frame hand-off between the game (back buffer) and the renderer (front buffer).
+    at most one frame waits in the queue: if the renderer falls behind, newer frames
+    are dropped instead of piling up. frames the renderer is done with come back
+    through a second queue, so the game can redraw into them instead of allocating.
+*/
+pub struct FrameSender<T> {
+    frames: SyncSender<T>,
+    free: Receiver<T>,
+    spare: Option<T>,
+    dropped: usize,
+}
+
+pub struct FrameReceiver<T> {
+    frames: Receiver<T>,
+    free: SyncSender<T>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Disconnected;
+
+// the buffers in flight are the one being drawn, the queued one, the shown one and the recycled ones
+const FREE_BUFFERS: usize = 2;
+
+pub fn frame_channel<T>() -> (FrameSender<T>, FrameReceiver<T>) {
+    let (frames_tx, frames_rx) = mpsc::sync_channel(1);
+    let (free_tx, free_rx) = mpsc::sync_channel(FREE_BUFFERS);
+    (
+        FrameSender {
+            frames: frames_tx,
+            free: free_rx,
+            spare: None,
+            dropped: 0,
+        },
+        FrameReceiver {
+            frames: frames_rx,
+            free: free_tx,
+        },
+    )
+}
+
+impl<T> FrameSender<T> {
+    // a recycled frame if there is one, a new one from `make` otherwise
+    pub fn acquire<F: FnOnce() -> T>(&mut self, make: F) -> T {
+        if let Some(frame) = self.spare.take() {
+            return frame;
+        }
+        match self.free.try_recv() {
+            Ok(frame) => frame,
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => make(),
+        }
+    }
+
+    /*
+        queues the frame for rendering without blocking.
+        Ok(false) means the renderer is still busy with the previous one:
+        the frame is dropped and kept for the next `acquire`.
+    */
+    pub fn submit(&mut self, frame: T) -> Result<bool, Disconnected> {
+        match self.frames.try_send(frame) {
+            Ok(()) => Ok(true),
+            Err(TrySendError::Full(frame)) => {
+                self.dropped += 1;
+                self.spare = Some(frame);
+                Ok(false)
+            }
+            Err(TrySendError::Disconnected(_)) => Err(Disconnected),
+        }
+    }
+
+    pub fn get_dropped_count(&self) -> usize {
+        self.dropped
+    }
+}
+
+impl<T> FrameReceiver<T> {
+    // blocks until the next frame, None once the sender is gone
+    pub fn recv(&self) -> Option<T> {
+        self.frames.recv().ok()
+    }
+
+    // gives a frame that is not needed anymore back to the sender
+    pub fn recycle(&self, frame: T) {
+        // if the free queue is full the frame is just released
+        let _ = self.free.try_send(frame);
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+// simulation never tries to catch up more than that many ticks in one go
+const MAX_TICKS_PER_UPDATE: u32 = 10;
+const STATS_WINDOW: Duration = Duration::from_secs(1);
+
+/*
+    fixed timestep driver:
+    `update` feeds the real elapsed time into an accumulator and runs
+    the simulation in equal `tick` steps, `should_render` caps the frame rate.
+    usage:
+        loop {
+            game_loop.update(|tick| world.update(tick));
+            if game_loop.should_render() {
+                draw();
+                game_loop.rendered();
+            }
+            game_loop.sleep();
+        }
+*/
+pub struct GameLoop {
+    tick: Duration,
+    frame: Duration,
+    accumulator: Duration,
+    last_update: Instant,
+    last_render: Option<Instant>,
+    stats_start: Instant,
+    frames: u32,
+    ticks: u32,
+    ticks_time: Duration,
+    fps: f32,
+    tick_time: Duration,
+}
+
+impl GameLoop {
+    // `tick_rate` is simulation ticks per second, `max_fps` is the render cap (0 - no cap)
+    pub fn new(tick_rate: u32, max_fps: u32) -> Self {
+        let now = Instant::now();
+        Self {
+            tick: Duration::from_secs(1) / tick_rate.max(1),
+            frame: if max_fps == 0 {
+                Duration::ZERO
+            } else {
+                Duration::from_secs(1) / max_fps
+            },
+            accumulator: Duration::ZERO,
+            last_update: now,
+            last_render: None,
+            stats_start: now,
+            frames: 0,
+            ticks: 0,
+            ticks_time: Duration::ZERO,
+            fps: 0.0,
+            tick_time: Duration::ZERO,
+        }
+    }
+
+    pub fn get_tick(&self) -> Duration {
+        self.tick
+    }
+
+    // runs `call` once per whole tick elapsed since the last update, returns the number of ticks
+    pub fn update<F: FnMut(Duration)>(&mut self, call: F) -> u32 {
+        let now = Instant::now();
+        let elapsed = now - self.last_update;
+        self.last_update = now;
+        self.advance(elapsed, call)
+    }
+
+    // same as `update`, but with the elapsed time given explicitly
+    pub fn advance<F: FnMut(Duration)>(&mut self, elapsed: Duration, mut call: F) -> u32 {
+        self.accumulator += elapsed;
+        let max_lag = self.tick * MAX_TICKS_PER_UPDATE;
+        if self.accumulator > max_lag {
+            self.accumulator = max_lag;
+        }
+        let mut count = 0;
+        while self.accumulator >= self.tick {
+            let started = Instant::now();
+            call(self.tick);
+            self.ticks_time += started.elapsed();
+            self.ticks += 1;
+            self.accumulator -= self.tick;
+            count += 1;
+        }
+        count
+    }
+
+    // how far the simulation is into the next tick, 0.0..1.0
+    pub fn get_alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.tick.as_secs_f32()
+    }
+
+    pub fn should_render(&self) -> bool {
+        match self.last_render {
+            Some(last) => last.elapsed() >= self.frame,
+            None => true,
+        }
+    }
+
+    pub fn rendered(&mut self) {
+        self.rendered_at(Instant::now());
+    }
+
+    // same as `rendered`, but with the time of the frame given explicitly
+    pub fn rendered_at(&mut self, now: Instant) {
+        self.last_render = Some(now);
+        self.frames += 1;
+        let window = now - self.stats_start;
+        if window >= STATS_WINDOW {
+            self.fps = self.frames as f32 / window.as_secs_f32();
+            self.tick_time = if self.ticks == 0 {
+                Duration::ZERO
+            } else {
+                self.ticks_time / self.ticks
+            };
+            self.frames = 0;
+            self.ticks = 0;
+            self.ticks_time = Duration::ZERO;
+            self.stats_start = now;
+        }
+    }
+
+    // until the next tick or the next frame, whatever comes first
+    pub fn time_to_wait(&self) -> Duration {
+        let to_tick = self
+            .tick
+            .saturating_sub(self.accumulator + self.last_update.elapsed());
+        let to_frame = match self.last_render {
+            Some(last) => self.frame.saturating_sub(last.elapsed()),
+            None => Duration::ZERO,
+        };
+        to_tick.min(to_frame)
+    }
+
+    pub fn sleep(&self) {
+        let wait = self.time_to_wait();
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    // frames per second measured over the last second
+    pub fn get_fps(&self) -> f32 {
+        self.fps
+    }
+
+    // average time a single simulation tick took over the last second
+    pub fn get_tick_time(&self) -> Duration {
+        self.tick_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn assert_alpha(game_loop: &GameLoop, alpha: f32) {
+        assert!((game_loop.get_alpha() - alpha).abs() < 1e-4, "alpha {}", game_loop.get_alpha());
+    }
+
+    #[test]
+    fn whole_ticks_and_the_rest() {
+        let mut game_loop = GameLoop::new(100, 0);
+        assert_eq!(game_loop.get_tick(), 10 * MS);
+        let mut ticks = Vec::new();
+        assert_eq!(game_loop.advance(25 * MS, |tick| ticks.push(tick)), 2);
+        assert_eq!(ticks, vec![10 * MS, 10 * MS]);
+        assert_alpha(&game_loop, 0.5);
+        assert_eq!(game_loop.advance(4 * MS, |_| {}), 0);
+        assert_alpha(&game_loop, 0.9);
+        assert_eq!(game_loop.advance(MS, |_| {}), 1);
+        assert_alpha(&game_loop, 0.0);
+        assert_eq!(game_loop.advance(Duration::ZERO, |_| {}), 0);
+    }
+
+    #[test]
+    fn catch_up_is_clamped() {
+        let mut game_loop = GameLoop::new(100, 0);
+        // a long stall runs MAX_TICKS_PER_UPDATE ticks and drops the rest
+        assert_eq!(game_loop.advance(Duration::from_secs(5), |_| {}), MAX_TICKS_PER_UPDATE);
+        assert_alpha(&game_loop, 0.0);
+        assert_eq!(game_loop.advance(105 * MS, |_| {}), MAX_TICKS_PER_UPDATE);
+        assert_alpha(&game_loop, 0.0);
+        // the lag below the limit is kept
+        assert_eq!(game_loop.advance(95 * MS, |_| {}), 9);
+        assert_alpha(&game_loop, 0.5);
+    }
+
+    #[test]
+    fn zero_tick_rate_is_one_tick_per_second() {
+        let mut game_loop = GameLoop::new(0, 0);
+        assert_eq!(game_loop.get_tick(), Duration::from_secs(1));
+        assert_eq!(game_loop.advance(Duration::from_millis(2500), |_| {}), 2);
+        assert_alpha(&game_loop, 0.5);
+    }
+
+    #[test]
+    fn stats_over_a_window() {
+        let mut game_loop = GameLoop::new(1000, 0);
+        let start = game_loop.stats_start;
+        assert_eq!(game_loop.advance(2 * MS, |_| thread::sleep(MS)), 2);
+        game_loop.rendered_at(start + 400 * MS);
+        game_loop.rendered_at(start + 800 * MS);
+        // nothing until the window is full
+        assert_eq!(game_loop.get_fps(), 0.0);
+        assert_eq!(game_loop.get_tick_time(), Duration::ZERO);
+        game_loop.rendered_at(start + 1500 * MS);
+        assert!((game_loop.get_fps() - 2.0).abs() < 1e-4);
+        assert!(game_loop.get_tick_time() >= MS);
+        // the next window starts empty
+        game_loop.rendered_at(start + 2500 * MS);
+        assert!((game_loop.get_fps() - 1.0).abs() < 1e-4);
+        assert_eq!(game_loop.get_tick_time(), Duration::ZERO);
+    }
+
+    #[test]
+    fn frame_cap() {
+        let mut uncapped = GameLoop::new(100, 0);
+        assert!(uncapped.should_render());
+        uncapped.rendered();
+        assert!(uncapped.should_render());
+        let mut capped = GameLoop::new(100, 1);
+        assert!(capped.should_render());
+        capped.rendered();
+        assert!(!capped.should_render());
+        assert!(capped.time_to_wait() <= 10 * MS);
+    }
+}
@@ -1,16 +1,19 @@
 use std::collections::HashSet;
 
 use crate::{
+    bit_matrix::BitMatrix,
     position::{
         Point, 
         IndexType, AsPoint
     }, 
     point_set::PointSet, 
-    canvas::Canvas
+    canvas::Canvas,
+    color::Color
 };
 
 pub trait GameObjectArea {
     fn get_point_set(&self) -> Option<&HashSet<Point>>;
+    fn get_bits(&self) -> Option<&BitMatrix>;
     fn get_pos(&self) -> Point;
     fn get_center_pos(&self) -> Point;
     fn move_to(&mut self, x: IndexType, y: IndexType);
@@ -23,6 +26,7 @@ pub trait GameObjectArea {
 pub struct GameObjectAreaImpl<T> {
     pub sprite: T,
     pos: Point,
+    color: Option<Color>,
 }
 
 impl<T> GameObjectAreaImpl<T> {
@@ -30,9 +34,18 @@ impl<T> GameObjectAreaImpl<T> {
         Self {
             sprite: point_set,
             pos: (x, y).as_point(), 
+            color: None,
         }
     }
 
+    pub fn set_color(&mut self, color: Option<Color>) {
+        self.color = color;
+    }
+
+    pub fn get_color(&self) -> Option<Color> {
+        self.color
+    }
+
     pub fn set_point_set(&mut self, ps: T) {
         self.sprite = ps;
     }
@@ -46,6 +59,10 @@ where
         self.sprite.get_point_set()
     }
 
+    fn get_bits(&self) -> Option<&BitMatrix> {
+        self.sprite.get_bits()
+    }
+
     fn get_pos(&self) -> Point {
         self.pos
     }
@@ -72,10 +89,12 @@ where
     }
 
     fn draw_to_canvas(&self, canvas: &mut dyn Canvas) {
-        if let Some(points) = self.get_point_set() {
-            for p in points.iter() {
-                canvas.draw_dot(self.pos.x + p.x, self.pos.y + p.y);
-            }
-        }
+        let mut draw = |p: Point| {
+            match self.color {
+                Some(color) => canvas.draw_dot_colored(self.pos.x + p.x, self.pos.y + p.y, color),
+                None => canvas.draw_dot(self.pos.x + p.x, self.pos.y + p.y),
+            };
+        };
+        self.sprite.for_each_drawn(&mut draw);
     }
 }
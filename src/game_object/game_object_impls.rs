@@ -1,4 +1,5 @@
-use crate::{game_object::GameObjectAreaImpl, animated::Animated, sprite::Sprite};
+use crate::{game_object::GameObjectAreaImpl, animated::Animated, animation_controller::AnimationController, sprite::Sprite};
 
 pub type GameObjectAnimated = GameObjectAreaImpl<Animated>;
+pub type GameObjectControlled = GameObjectAreaImpl<AnimationController>;
 pub type GameObjectStatic = GameObjectAreaImpl<Sprite>;
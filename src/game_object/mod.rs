@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod game_object;
 pub mod game_object_area;
 pub mod game_object_impls;
@@ -7,3 +8,4 @@ pub use game_object_area::GameObjectArea;
 pub use game_object_area::GameObjectAreaImpl;
 pub use game_object_impls::GameObjectStatic;
 pub use game_object_impls::GameObjectAnimated;
+pub use game_object_impls::GameObjectControlled;
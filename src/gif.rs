@@ -0,0 +1,267 @@
+use std::{collections::HashMap, io::Write};
+
+/*
+    minimal GIF89a writer: looped animation, one full-size LZW-compressed image per frame.
+    every frame carries its own color table, so frames can be written
+    while the palette still grows.
+*/
+
+const MAX_CODE: u16 = 4096;
+const MAX_CODE_SIZE: u8 = 12;
+
+// smallest n so that 2^n >= colors, never less than 1 as the format requires
+fn table_bits(colors: usize) -> u8 {
+    let mut bits = 1;
+    while (1usize << bits) < colors {
+        bits += 1;
+    }
+    bits
+}
+
+pub fn write_header<W: Write>(
+    out: &mut W,
+    width: u16,
+    height: u16,
+    palette: &[[u8; 3]],
+) -> std::io::Result<()> {
+    let bits = table_bits(palette.len());
+    out.write_all(b"GIF89a")?;
+    out.write_all(&width.to_le_bytes())?;
+    out.write_all(&height.to_le_bytes())?;
+    // global table present, 8 bit color resolution, table size
+    out.write_all(&[0x80 | 0x70 | (bits - 1), 0, 0])?;
+    write_table(out, bits, palette)?;
+    // NETSCAPE2.0 application block, loop forever
+    out.write_all(&[0x21, 0xFF, 0x0B])?;
+    out.write_all(b"NETSCAPE2.0")?;
+    out.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])
+}
+
+fn write_table<W: Write>(out: &mut W, bits: u8, palette: &[[u8; 3]]) -> std::io::Result<()> {
+    for id in 0..(1usize << bits) {
+        out.write_all(palette.get(id).unwrap_or(&[0, 0, 0]))?;
+    }
+    Ok(())
+}
+
+// `delay` is in hundredths of a second
+pub fn write_frame<W: Write>(
+    out: &mut W,
+    width: u16,
+    height: u16,
+    palette: &[[u8; 3]],
+    delay: u16,
+    pixels: &[u8],
+) -> std::io::Result<()> {
+    let bits = table_bits(palette.len());
+    out.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+    out.write_all(&delay.to_le_bytes())?;
+    out.write_all(&[0x00, 0x00])?;
+
+    out.write_all(&[0x2C, 0, 0, 0, 0])?;
+    out.write_all(&width.to_le_bytes())?;
+    out.write_all(&height.to_le_bytes())?;
+    // local table present, its size
+    out.write_all(&[0x80 | (bits - 1)])?;
+    write_table(out, bits, palette)?;
+
+    let min_code_size = bits.max(2);
+    out.write_all(&[min_code_size])?;
+    for block in lzw_encode(pixels, min_code_size).chunks(255) {
+        out.write_all(&[block.len() as u8])?;
+        out.write_all(block)?;
+    }
+    out.write_all(&[0x00])
+}
+
+pub fn write_trailer<W: Write>(out: &mut W) -> std::io::Result<()> {
+    out.write_all(&[0x3B])
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            current: 0,
+            bits: 0,
+        }
+    }
+
+    fn write(&mut self, code: u16, size: u8) {
+        self.current |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.current as u8);
+            self.current >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+pub fn lzw_encode(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear: u16 = 1 << min_code_size;
+    let end = clear + 1;
+    let mut out = BitWriter::new();
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = min_code_size + 1;
+    let mut next = clear + 2;
+
+    out.write(clear, size);
+    let mut iter = pixels.iter();
+    let mut prefix = match iter.next() {
+        Some(p) => *p as u16,
+        None => {
+            out.write(end, size);
+            return out.finish();
+        }
+    };
+    for &k in iter {
+        if let Some(&code) = dict.get(&(prefix, k)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, size);
+        // the decoder grows its code size one entry later than we add them
+        if next == (1 << size) && size < MAX_CODE_SIZE {
+            size += 1;
+        }
+        if next < MAX_CODE {
+            dict.insert((prefix, k), next);
+            next += 1;
+        } else {
+            out.write(clear, size);
+            dict.clear();
+            size = min_code_size + 1;
+            next = clear + 2;
+        }
+        prefix = k as u16;
+    }
+    out.write(prefix, size);
+    if next == (1 << size) && size < MAX_CODE_SIZE {
+        size += 1;
+    }
+    out.write(end, size);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reads the codes back the way a GIF decoder does
+    fn lzw_decode(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+        let mut dict: Vec<Vec<u8>> = Vec::new();
+        let reset = |dict: &mut Vec<Vec<u8>>| {
+            dict.clear();
+            dict.extend((0..clear).map(|c| vec![c as u8]));
+            // the clear and the end codes
+            dict.push(Vec::new());
+            dict.push(Vec::new());
+        };
+        reset(&mut dict);
+        let mut size = min_code_size + 1;
+        let mut prev: Option<usize> = None;
+        let mut out = Vec::new();
+        let (mut pos, mut current, mut bits) = (0, 0u32, 0u8);
+        loop {
+            while bits < size {
+                current |= (bytes[pos] as u32) << bits;
+                pos += 1;
+                bits += 8;
+            }
+            let code = (current & ((1 << size) - 1)) as usize;
+            current >>= size;
+            bits -= size;
+
+            if code == clear {
+                reset(&mut dict);
+                size = min_code_size + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (prev, code < dict.len()) {
+                (_, true) => dict[code].clone(),
+                (Some(prev), false) => {
+                    assert_eq!(code, dict.len(), "code out of the table");
+                    let mut entry = dict[prev].clone();
+                    entry.push(dict[prev][0]);
+                    entry
+                }
+                (None, false) => panic!("unknown first code {}", code),
+            };
+            if let Some(prev) = prev {
+                if dict.len() < MAX_CODE as usize {
+                    let mut added = dict[prev].clone();
+                    added.push(entry[0]);
+                    dict.push(added);
+                }
+            }
+            if dict.len() == (1 << size) && size < MAX_CODE_SIZE {
+                size += 1;
+            }
+            out.extend_from_slice(&entry);
+            prev = Some(code);
+        }
+    }
+
+    fn round_trip(pixels: &[u8], min_code_size: u8) {
+        let decoded = lzw_decode(&lzw_encode(pixels, min_code_size), min_code_size);
+        assert_eq!(decoded.len(), pixels.len());
+        assert!(decoded == pixels);
+    }
+
+    fn noise(len: usize, colors: u32) -> Vec<u8> {
+        let mut seed: u32 = 7;
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                ((seed >> 16) % colors) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_and_single_pixel() {
+        round_trip(&[], 2);
+        round_trip(&[3], 2);
+    }
+
+    #[test]
+    fn long_runs_grow_the_code_size() {
+        // the codes get longer and longer, the table fills slowly
+        let pixels: Vec<u8> = (0..20000).map(|i| ((i / 700) % 2) as u8).collect();
+        round_trip(&pixels, 2);
+    }
+
+    #[test]
+    fn noise_fills_the_table_and_resets() {
+        // enough distinct pairs to pass 4096 codes a few times
+        round_trip(&noise(100_000, 4), 2);
+        round_trip(&noise(100_000, 256), 8);
+    }
+
+    #[test]
+    fn every_code_size() {
+        for bits in 2..=8u8 {
+            round_trip(&noise(30_000, 1 << bits), bits);
+        }
+    }
+}
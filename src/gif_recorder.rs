@@ -0,0 +1,252 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    color::{self, CellColor},
+    gif,
+    position::IndexType,
+    terminal_canvas::TerminalCanvas,
+};
+
+const DEFAULT_FG: [u8; 3] = [192, 192, 192];
+const DEFAULT_BG: [u8; 3] = [0, 0, 0];
+const MAX_COLORS: usize = 256;
+// GIF delays are in hundredths of a second and most players ignore anything below 2
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+const LAST_FRAME_DELAY: u16 = 100;
+// captured frames waiting for the encoder, the newer ones are dropped when it falls behind
+const QUEUE_FRAMES: usize = 4;
+
+// the dots and the cell colors of a canvas, cheap to take on the render thread
+struct Snapshot {
+    timestamp: Duration,
+    cells: (usize, usize),
+    cell_dots: (usize, usize),
+    colors: Vec<CellColor>,
+    dots: Vec<bool>,
+}
+
+struct Frame {
+    timestamp: Duration,
+    pixels: Vec<u8>,
+}
+
+/*
+    records canvas frames to a GIF, every dot becomes a `dot_size` x `dot_size` square.
+    capture only copies the canvas, the frames are rasterized, encoded and written
+    on a thread of their own as they come. frames coming faster than GIF can show
+    replace the previous one, unchanged frames are dropped.
+*/
+pub struct GifRecorder<W: Write + Send + 'static> {
+    frames: Option<SyncSender<Snapshot>>,
+    encoder: JoinHandle<io::Result<W>>,
+    dropped: usize,
+}
+
+impl GifRecorder<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P, dot_size: usize) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?), dot_size))
+    }
+}
+
+impl<W: Write + Send + 'static> GifRecorder<W> {
+    pub fn new(out: W, dot_size: usize) -> Self {
+        let (frames, received) = mpsc::sync_channel(QUEUE_FRAMES);
+        let encoder = thread::spawn(move || Encoder::new(out, dot_size).run(received));
+        Self {
+            frames: Some(frames),
+            encoder,
+            dropped: 0,
+        }
+    }
+
+    // frames lost because the encoder was busy
+    pub fn get_dropped(&self) -> usize {
+        self.dropped
+    }
+
+    // `timestamp` is the time since the recording started
+    pub fn capture<C: TerminalCanvas>(&mut self, canvas: &C, timestamp: Duration) {
+        let Some(frames) = self.frames.as_ref() else {
+            return;
+        };
+        match frames.try_send(snapshot(canvas, timestamp)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            // the encoder failed, finish reports why
+            Err(TrySendError::Disconnected(_)) => self.frames = None,
+        }
+    }
+
+    // waits for the queued frames and ends the file
+    pub fn finish(mut self) -> io::Result<W> {
+        self.frames = None;
+        self.encoder
+            .join()
+            .map_err(|_| io::Error::other("the GIF encoder panicked"))?
+    }
+}
+
+fn snapshot<C: TerminalCanvas>(canvas: &C, timestamp: Duration) -> Snapshot {
+    let (cells_x, cells_y) = canvas.get_cells_size();
+    let (dots_x, dots_y) = canvas.get_cell_dots();
+    let (width, height) = (cells_x * dots_x, cells_y * dots_y);
+    let mut colors = Vec::with_capacity(cells_x * cells_y);
+    for cy in 0..cells_y {
+        for cx in 0..cells_x {
+            colors.push(canvas.get_cell_color(cx, cy));
+        }
+    }
+    let mut dots = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            dots.push(canvas.get_dot(x as IndexType, y as IndexType) == Some(true));
+        }
+    }
+    Snapshot {
+        timestamp,
+        cells: (cells_x, cells_y),
+        cell_dots: (dots_x, dots_y),
+        colors,
+        dots,
+    }
+}
+
+/*
+    holds back only the last frame, its delay is known when the next one comes.
+    the size of the file is taken from the first frame.
+*/
+struct Encoder<W: Write> {
+    out: W,
+    dot_size: usize,
+    width: usize,
+    height: usize,
+    palette: Vec<[u8; 3]>,
+    last: Option<Frame>,
+    started: bool,
+}
+
+impl<W: Write> Encoder<W> {
+    fn new(out: W, dot_size: usize) -> Self {
+        Self {
+            out,
+            dot_size: dot_size.max(1),
+            width: 0,
+            height: 0,
+            palette: vec![DEFAULT_BG, DEFAULT_FG],
+            last: None,
+            started: false,
+        }
+    }
+
+    fn run(mut self, frames: Receiver<Snapshot>) -> io::Result<W> {
+        for snapshot in frames {
+            self.push(snapshot)?;
+        }
+        self.finish()
+    }
+
+    fn push(&mut self, snapshot: Snapshot) -> io::Result<()> {
+        if !self.started {
+            let (cells_x, cells_y) = snapshot.cells;
+            let (dots_x, dots_y) = snapshot.cell_dots;
+            self.width = cells_x * dots_x * self.dot_size;
+            self.height = cells_y * dots_y * self.dot_size;
+            self.write_header()?;
+        }
+        let pixels = self.rasterize(&snapshot);
+        let timestamp = snapshot.timestamp;
+        if let Some(last) = self.last.as_mut() {
+            if last.pixels == pixels {
+                return Ok(());
+            }
+            if timestamp.saturating_sub(last.timestamp) < MIN_FRAME_DELAY {
+                last.pixels = pixels;
+                return Ok(());
+            }
+        }
+        if let Some(last) = self.last.replace(Frame { timestamp, pixels }) {
+            let millis = timestamp.saturating_sub(last.timestamp).as_millis();
+            let delay = (millis / 10).clamp(2, u16::MAX as u128) as u16;
+            self.write_frame(&last, delay)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        if !self.started {
+            self.write_header()?;
+        }
+        if let Some(last) = self.last.take() {
+            self.write_frame(&last, LAST_FRAME_DELAY)?;
+        }
+        gif::write_trailer(&mut self.out)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if self.width > u16::MAX as usize || self.height > u16::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame is too big for GIF"));
+        }
+        self.started = true;
+        gif::write_header(&mut self.out, self.width as u16, self.height as u16, &self.palette)
+    }
+
+    fn write_frame(&mut self, frame: &Frame, delay: u16) -> io::Result<()> {
+        let (width, height) = (self.width as u16, self.height as u16);
+        gif::write_frame(&mut self.out, width, height, &self.palette, delay, &frame.pixels)
+    }
+
+    fn color_id(&mut self, rgb: [u8; 3]) -> u8 {
+        if let Some(id) = self.palette.iter().position(|c| *c == rgb) {
+            return id as u8;
+        }
+        if self.palette.len() < MAX_COLORS {
+            self.palette.push(rgb);
+            return (self.palette.len() - 1) as u8;
+        }
+        let distance = |c: &[u8; 3]| -> i32 {
+            (0..3).map(|i| (c[i] as i32 - rgb[i] as i32).pow(2)).sum()
+        };
+        self.palette
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, c)| distance(c))
+            .map_or(0, |(id, _)| id as u8)
+    }
+
+    fn rasterize(&mut self, snapshot: &Snapshot) -> Vec<u8> {
+        let (cells_x, cells_y) = snapshot.cells;
+        let (dots_x, dots_y) = snapshot.cell_dots;
+        let row = cells_x * dots_x;
+        // a canvas of a different size is cropped or padded to the first one
+        let mut pixels = vec![0; self.width * self.height];
+        for cx in 0..cells_x {
+            for cy in 0..cells_y {
+                let cell = snapshot.colors[cy * cells_x + cx];
+                let fg = self.color_id(color::to_rgb(cell.fg, DEFAULT_FG));
+                let bg = self.color_id(color::to_rgb(cell.bg, DEFAULT_BG));
+                for dx in 0..dots_x {
+                    for dy in 0..dots_y {
+                        let (x, y) = (cx * dots_x + dx, cy * dots_y + dy);
+                        let id = if snapshot.dots[y * row + x] { fg } else { bg };
+                        let (px, py) = (x * self.dot_size, y * self.dot_size);
+                        for y in py..(py + self.dot_size).min(self.height) {
+                            for x in px..(px + self.dot_size).min(self.width) {
+                                pixels[y * self.width + x] = id;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        pixels
+    }
+}
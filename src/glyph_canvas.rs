@@ -0,0 +1,125 @@
+use std::marker::PhantomData;
+
+use crate::{
+    canvas::Canvas,
+    color::{CellColor, Color},
+    position::IndexType,
+    terminal_canvas::TerminalCanvas,
+};
+
+/*
+    describes how a terminal cell is split into dots.
+    dot (x, y) of a cell is the bit `y * WIDTH + x` of its mask.
+*/
+pub trait Glyphs {
+    const WIDTH: usize;
+    const HEIGHT: usize;
+    fn to_char(mask: u8) -> char;
+}
+
+// cells are stored row by row in one flat buffer
+pub struct GlyphCanvas<G: Glyphs> {
+    area: Vec<u8>,
+    colors: Vec<CellColor>,
+    width: usize,
+    height: usize,
+    glyphs: PhantomData<G>,
+}
+
+impl<G: Glyphs> GlyphCanvas<G> {
+    pub fn new(x: usize, y: usize) -> Self {
+        let x_fix = x.div_ceil(G::WIDTH);
+        let y_fix = y.div_ceil(G::HEIGHT);
+        Self {
+            area: vec![0; x_fix * y_fix],
+            colors: vec![CellColor::default(); x_fix * y_fix],
+            width: x_fix,
+            height: y_fix,
+            glyphs: PhantomData,
+        }
+    }
+
+    pub fn area(&self) -> &[u8] {
+        &self.area
+    }
+
+    pub fn get_cell(&self, x: usize, y: usize) -> u8 {
+        self.area[y * self.width + x]
+    }
+
+    fn to_cell(&self, x: IndexType, y: IndexType) -> Option<(usize, u8)> {
+        if x >= 0 && y >= 0 {
+            let (x, y) = (x as usize, y as usize);
+            let (fix_x, fix_y) = (x / G::WIDTH, y / G::HEIGHT);
+            if fix_x < self.width && fix_y < self.height {
+                let bit = (y % G::HEIGHT) * G::WIDTH + x % G::WIDTH;
+                return Some((fix_y * self.width + fix_x, 1 << bit));
+            }
+        }
+        None
+    }
+}
+
+impl<G: Glyphs> Canvas for GlyphCanvas<G> {
+    fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        if let Some((id, bit)) = self.to_cell(x, y) {
+            self.area[id] |= bit;
+            return true;
+        }
+        false
+    }
+
+    fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        if let Some((id, bit)) = self.to_cell(x, y) {
+            self.area[id] &= !bit;
+            return true;
+        }
+        false
+    }
+
+    fn get_dot(&self, x: IndexType, y: IndexType) -> Option<bool> {
+        self.to_cell(x, y).map(|(id, bit)| self.area[id] & bit != 0)
+    }
+
+    fn draw_dot_colored(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        if let Some((id, _)) = self.to_cell(x, y) {
+            self.colors[id] = self.colors[id].with_fg(color);
+        }
+        self.draw_dot(x, y)
+    }
+
+    fn set_background(&mut self, x: IndexType, y: IndexType, color: Color) -> bool {
+        if let Some((id, _)) = self.to_cell(x, y) {
+            self.colors[id] = self.colors[id].with_bg(color);
+            return true;
+        }
+        false
+    }
+}
+
+impl<G: Glyphs> TerminalCanvas for GlyphCanvas<G> {
+    fn new(x: usize, y: usize) -> Self {
+        GlyphCanvas::new(x, y)
+    }
+
+    fn clear(&mut self) {
+        self.area.fill(0);
+        self.colors.fill(CellColor::default());
+    }
+
+    fn get_cells_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn get_cell_dots(&self) -> (usize, usize) {
+        (G::WIDTH, G::HEIGHT)
+    }
+
+    fn get_cell_char(&self, x: usize, y: usize) -> char {
+        G::to_char(self.get_cell(x, y))
+    }
+
+    fn get_cell_color(&self, x: usize, y: usize) -> CellColor {
+        self.colors[y * self.width + x]
+    }
+}
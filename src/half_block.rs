@@ -0,0 +1,17 @@
+use crate::glyph_canvas::{GlyphCanvas, Glyphs};
+
+// one column, two rows of dots per cell: ' ', '▀', '▄', '█'
+pub struct HalfBlock;
+
+const CHARS: &[char] = &[' ', '\u{2580}', '\u{2584}', '\u{2588}'];
+
+impl Glyphs for HalfBlock {
+    const WIDTH: usize = 1;
+    const HEIGHT: usize = 2;
+
+    fn to_char(mask: u8) -> char {
+        CHARS[(mask & 0x03) as usize]
+    }
+}
+
+pub type HalfBlockCanvas = GlyphCanvas<HalfBlock>;
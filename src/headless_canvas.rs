@@ -0,0 +1,60 @@
+use crate::{canvas::Canvas, position::IndexType};
+
+// plain in-memory dot grid, nothing is ever sent to a terminal
+pub struct HeadlessCanvas {
+    width: usize,
+    height: usize,
+    dots: Vec<bool>,
+}
+
+impl HeadlessCanvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            dots: vec![false; width * height],
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn clear(&mut self) {
+        self.dots.fill(false);
+    }
+
+    fn to_id(&self, x: IndexType, y: IndexType) -> Option<usize> {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            Some(y as usize * self.width + x as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl Canvas for HeadlessCanvas {
+    fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        if let Some(id) = self.to_id(x, y) {
+            self.dots[id] = true;
+            return true;
+        }
+        false
+    }
+
+    fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+        if let Some(id) = self.to_id(x, y) {
+            self.dots[id] = false;
+            return true;
+        }
+        false
+    }
+
+    fn get_dot(&self, x: IndexType, y: IndexType) -> Option<bool> {
+        self.to_id(x, y).map(|id| self.dots[id])
+    }
+}
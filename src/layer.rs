@@ -0,0 +1,23 @@
+// draw order, bottom to top
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+pub enum Layer {
+    Ground,
+    Objects,
+    Overhead,
+    Effects,
+    Hud,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 5] = [
+        Layer::Ground,
+        Layer::Objects,
+        Layer::Overhead,
+        Layer::Effects,
+        Layer::Hud,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
@@ -0,0 +1,88 @@
+use std::cmp::min;
+
+/*
+    where and how big the canvas is on the terminal.
+    the canvas takes as much of the window as it can, but never more than `max_size`;
+    whatever is left is split evenly around it (letterbox).
+    a window too small for a useful view can show a bigger one scaled down.
+*/
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct CanvasLayout {
+    // canvas size in dots
+    pub width: usize,
+    pub height: usize,
+    // top left cell of the canvas (column, row)
+    pub origin: (u16, u16),
+    // view dots per canvas dot, 1 shows the view as it is
+    pub scale: usize,
+}
+
+impl CanvasLayout {
+    /*
+        `terminal` - window size in cells (columns, rows)
+        `reserved_rows` - rows under the canvas kept for status lines
+        `cell_dots` - dots per cell of the canvas backend
+        `max_size` - the biggest canvas worth having, in dots
+    */
+    pub fn fit(
+        terminal: (u16, u16),
+        reserved_rows: u16,
+        cell_dots: (usize, usize),
+        max_size: (usize, usize),
+    ) -> Self {
+        let (dots_x, dots_y) = (cell_dots.0.max(1), cell_dots.1.max(1));
+        let free_cols = terminal.0 as usize;
+        let free_rows = terminal.1.saturating_sub(reserved_rows) as usize;
+        let cols = min(free_cols, max_size.0.div_ceil(dots_x)).max(1);
+        let rows = min(free_rows, max_size.1.div_ceil(dots_y)).max(1);
+        let width = min(cols * dots_x, max_size.0);
+        let height = min(rows * dots_y, max_size.1);
+        let origin = (
+            (free_cols.saturating_sub(cols) / 2) as u16,
+            (free_rows.saturating_sub(rows) / 2) as u16,
+        );
+        Self {
+            width,
+            height,
+            origin,
+            scale: 1,
+        }
+    }
+
+    /*
+        same as `fit`, but a canvas smaller than `min_view` shows at least
+        `min_view` dots of the world, every canvas dot stands for `scale` x `scale` of them.
+    */
+    pub fn fit_scaled(
+        terminal: (u16, u16),
+        reserved_rows: u16,
+        cell_dots: (usize, usize),
+        max_size: (usize, usize),
+        min_view: (usize, usize),
+    ) -> Self {
+        let mut layout = Self::fit(terminal, reserved_rows, cell_dots, max_size);
+        let min_view = (min(min_view.0, max_size.0), min(min_view.1, max_size.1));
+        layout.scale = min_view
+            .0
+            .div_ceil(layout.width)
+            .max(min_view.1.div_ceil(layout.height))
+            .max(1);
+        layout
+    }
+
+    // the part of the world the canvas shows, in dots
+    pub fn get_view_size(&self, max_size: (usize, usize)) -> (usize, usize) {
+        (
+            min(self.width * self.scale, max_size.0),
+            min(self.height * self.scale, max_size.1),
+        )
+    }
+
+    // canvas size in cells
+    pub fn get_cells_size(&self, cell_dots: (usize, usize)) -> (usize, usize) {
+        (
+            self.width.div_ceil(cell_dots.0.max(1)),
+            self.height.div_ceil(cell_dots.1.max(1)),
+        )
+    }
+}
@@ -1,17 +1,42 @@
 pub mod animated;
+pub mod animation_controller;
 pub mod animation_builder;
+pub mod ascii;
+pub mod asciicast;
+pub mod bit_matrix;
 pub mod braille;
 pub mod braille_canvas;
+pub mod camera;
 pub mod canvas;
+pub mod color;
+pub mod compositor;
 pub mod direction;
 pub mod drawable;
+pub mod font;
+pub mod frame_channel;
+pub mod game_loop;
 pub mod game_object;
+pub mod gif;
+pub mod gif_recorder;
+pub mod glyph_canvas;
+pub mod half_block;
+pub mod headless_canvas;
+pub mod layer;
+pub mod layout;
+pub mod line;
+pub mod netpbm;
 pub mod obstacle;
 pub mod point_set;
 pub mod position;
 pub mod render;
+pub mod sextant;
 pub mod shot;
+pub mod snapshot;
 pub mod sprite;
+pub mod sprite_asset;
+pub mod sprite_parser;
+pub mod sprite_registry;
 pub mod tank;
 pub mod terminal;
+pub mod terminal_canvas;
 pub mod timer;
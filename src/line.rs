@@ -0,0 +1,282 @@
+use crate::position::{AsPoint, IndexType, Point};
+
+/*
+    integer Bresenham walk from `from` to `to`, both ends included.
+    works in every octant.
+*/
+pub struct Line {
+    current: Point,
+    to: Point,
+    dx: i32,
+    dy: i32,
+    step_x: IndexType,
+    step_y: IndexType,
+    error: i32,
+    done: bool,
+}
+
+impl Line {
+    pub fn new(from: Point, to: Point) -> Self {
+        let dx = (to.x as i32 - from.x as i32).abs();
+        let dy = -(to.y as i32 - from.y as i32).abs();
+        Self {
+            current: from,
+            to,
+            dx,
+            dy,
+            step_x: if from.x < to.x { 1 } else { -1 },
+            step_y: if from.y < to.y { 1 } else { -1 },
+            error: dx + dy,
+            done: false,
+        }
+    }
+
+    // true if the line advances along x more than along y
+    pub fn is_x_major(&self) -> bool {
+        self.dx >= -self.dy
+    }
+}
+
+impl Iterator for Line {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.done {
+            return None;
+        }
+        let res = self.current;
+        if self.current == self.to {
+            self.done = true;
+            return Some(res);
+        }
+        let e2 = 2 * self.error;
+        if e2 >= self.dy {
+            self.error += self.dy;
+            self.current.x += self.step_x;
+        }
+        if e2 <= self.dx {
+            self.error += self.dx;
+            self.current.y += self.step_y;
+        }
+        Some(res)
+    }
+}
+
+#[derive(Clone)]
+pub struct LineStyle {
+    width: IndexType,
+    pattern: Vec<bool>,
+}
+
+impl LineStyle {
+    pub fn solid() -> Self {
+        Self {
+            width: 1,
+            pattern: vec![true],
+        }
+    }
+
+    pub fn dashed(on: usize, off: usize) -> Self {
+        let mut pattern = vec![true; on.max(1)];
+        pattern.resize(on.max(1) + off, false);
+        Self { width: 1, pattern }
+    }
+
+    pub fn dotted() -> Self {
+        Self::dashed(1, 1)
+    }
+
+    /*
+        '*' (or any other char) - draw, ' ' or '.' - skip
+        example: "***  *  " is a dash-dot pattern
+    */
+    pub fn from_pattern(value: &str) -> Self {
+        let mut pattern: Vec<bool> = value.chars().map(|c| c != ' ' && c != '.').collect();
+        if pattern.is_empty() {
+            pattern.push(true);
+        }
+        Self { width: 1, pattern }
+    }
+
+    pub fn with_width(mut self, width: IndexType) -> Self {
+        self.width = width.max(1);
+        self
+    }
+
+    pub fn get_width(&self) -> IndexType {
+        self.width
+    }
+
+    pub fn is_on(&self, step: usize) -> bool {
+        self.pattern[step % self.pattern.len()]
+    }
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self::solid()
+    }
+}
+
+// offsets across the stroke: width 3 gives -1..=1, width 4 gives -1..=2
+pub fn stroke_offsets(width: IndexType) -> impl Iterator<Item = IndexType> {
+    let from = -(width - 1) / 2;
+    from..from + width.max(1)
+}
+
+pub fn stroke_point(p: Point, offset: IndexType, x_major: bool) -> Point {
+    if x_major {
+        (p.x, p.y + offset).as_point()
+    } else {
+        (p.x + offset, p.y).as_point()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::Canvas;
+    use std::collections::HashSet;
+
+    type Dot = (IndexType, IndexType);
+
+    // remembers the drawn dots, `width` dots of a row are shown
+    struct Dots {
+        width: IndexType,
+        dots: HashSet<Dot>,
+    }
+
+    impl Dots {
+        fn new(width: IndexType) -> Self {
+            Self {
+                width,
+                dots: HashSet::new(),
+            }
+        }
+
+        fn row(&self, y: IndexType) -> String {
+            (0..self.width)
+                .map(|x| if self.dots.contains(&(x, y)) { '*' } else { ' ' })
+                .collect()
+        }
+    }
+
+    impl Canvas for Dots {
+        fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+            self.dots.insert((x, y))
+        }
+
+        fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
+            self.dots.remove(&(x, y))
+        }
+    }
+
+    fn walk(from: Dot, to: Dot) -> Vec<Dot> {
+        Line::new(from.as_point(), to.as_point()).map(|p| (p.x, p.y)).collect()
+    }
+
+    // both ends, one step along the major axis at a time, never more than half a dot off
+    fn check(from: Dot, to: Dot) {
+        let points = walk(from, to);
+        let (dx, dy) = ((to.0 - from.0) as i32, (to.1 - from.1) as i32);
+        assert_eq!(points.first(), Some(&from));
+        assert_eq!(points.last(), Some(&to));
+        assert_eq!(points.len() as i32, dx.abs().max(dy.abs()) + 1);
+        for pair in points.windows(2) {
+            let (sx, sy) = (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+            assert!(sx.abs() <= 1 && sy.abs() <= 1);
+            if dx.abs() >= dy.abs() {
+                assert_eq!(sx as i32, dx.signum());
+            } else {
+                assert_eq!(sy as i32, dy.signum());
+            }
+        }
+        for p in points.iter() {
+            let (px, py) = ((p.0 - from.0) as i32, (p.1 - from.1) as i32);
+            // the distance along the minor axis times the major length
+            let off = (px * dy - py * dx).abs();
+            assert!(2 * off <= dx.abs().max(dy.abs()), "{:?} -> {:?} at {:?}", from, to, p);
+        }
+    }
+
+    #[test]
+    fn every_octant() {
+        let ends = [(5, 2), (2, 5), (-2, 5), (-5, 2), (-5, -2), (-2, -5), (2, -5), (5, -2)];
+        for to in ends {
+            check((3, 4), (3 + to.0, 4 + to.1));
+        }
+        // the axes and the diagonals between the octants
+        for to in [(6, 0), (0, 6), (-6, 0), (0, -6), (4, 4), (-4, 4), (-4, -4), (4, -4)] {
+            check((0, 0), to);
+        }
+        assert_eq!(walk((0, 0), (5, 2)), vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]);
+        let steep = vec![(0, 0), (0, -1), (-1, -2), (-1, -3), (-2, -4), (-2, -5)];
+        assert_eq!(walk((0, 0), (-2, -5)), steep);
+    }
+
+    #[test]
+    fn reversed_ends() {
+        for to in [(5, 2), (2, 5), (-2, 5), (-5, 2), (7, -3), (-1, -6)] {
+            check(to, (0, 0));
+            let mut back = walk(to, (0, 0));
+            back.reverse();
+            // the ties go the other way, the dots stay close
+            for (a, b) in walk((0, 0), to).iter().zip(back.iter()) {
+                assert!((a.0 - b.0).abs() <= 1 && (a.1 - b.1).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_length() {
+        assert_eq!(walk((3, -2), (3, -2)), vec![(3, -2)]);
+        assert!(Line::new((0, 0).as_point(), (0, 0).as_point()).is_x_major());
+    }
+
+    #[test]
+    fn major_axis() {
+        assert!(Line::new((0, 0).as_point(), (5, 5).as_point()).is_x_major());
+        assert!(Line::new((0, 0).as_point(), (-5, 2).as_point()).is_x_major());
+        assert!(!Line::new((0, 0).as_point(), (2, -5).as_point()).is_x_major());
+    }
+
+    fn pattern(style: &LineStyle, steps: usize) -> String {
+        (0..steps).map(|step| if style.is_on(step) { '*' } else { ' ' }).collect()
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(pattern(&LineStyle::solid(), 4), "****");
+        assert_eq!(pattern(&LineStyle::dotted(), 5), "* * *");
+        assert_eq!(pattern(&LineStyle::dashed(3, 2), 10), "***  ***  ");
+        // a dash is never empty
+        assert_eq!(pattern(&LineStyle::dashed(0, 2), 6), "*  *  ");
+        assert_eq!(pattern(&LineStyle::from_pattern("**. x"), 10), "**  ***  *");
+        assert_eq!(pattern(&LineStyle::from_pattern(""), 3), "***");
+        assert_eq!(LineStyle::dotted().with_width(0).get_width(), 1);
+    }
+
+    #[test]
+    fn the_pattern_starts_at_the_first_end() {
+        let style = LineStyle::dashed(2, 1);
+        let mut canvas = Dots::new(8);
+        canvas.draw_line_styled((0, 0).as_point(), (7, 0).as_point(), &style);
+        canvas.draw_line_styled((7, 1).as_point(), (0, 1).as_point(), &style);
+        assert_eq!(canvas.row(0), "** ** **");
+        assert_eq!(canvas.row(1), "** ** **");
+        let mut canvas = Dots::new(7);
+        canvas.draw_line_styled((6, 0).as_point(), (0, 0).as_point(), &style);
+        assert_eq!(canvas.row(0), "* ** **");
+    }
+
+    #[test]
+    fn wide_lines_go_across() {
+        assert_eq!(stroke_offsets(3).collect::<Vec<_>>(), vec![-1, 0, 1]);
+        assert_eq!(stroke_offsets(4).collect::<Vec<_>>(), vec![-1, 0, 1, 2]);
+        let mut canvas = Dots::new(5);
+        let style = LineStyle::dotted().with_width(3);
+        canvas.draw_line_styled((2, 0).as_point(), (2, 4).as_point(), &style);
+        let rows: Vec<String> = (0..5).map(|y| canvas.row(y)).collect();
+        assert_eq!(rows, vec![" *** ", "     ", " *** ", "     ", " *** "]);
+    }
+}
@@ -1,45 +1,218 @@
 use std::{
-    io,
-    sync::mpsc,
+    env,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
     thread,
     time::{Duration, Instant},
 };
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::{
+    event::{self, Event, KeyCode},
+    style::Color,
+};
 use tank::{
+    ascii::AsciiCanvas,
+    asciicast::CastWriter,
     braille_canvas::BrailleCanvas,
+    camera::{Camera, ScaledView},
+    canvas::Canvas,
+    color::CellColor,
+    compositor::Compositor,
     drawable::Drawable,
+    font::{TextAlign, TextStyle, FONT_3X5},
+    frame_channel::frame_channel,
+    game_loop::GameLoop,
+    game_object::GameObject,
+    gif_recorder::GifRecorder,
+    half_block::HalfBlockCanvas,
+    layer::Layer,
+    layout::CanvasLayout,
     obstacle::{Obstacle, Obstacles},
-    position::IndexType,
+    position::{IndexType, Point},
     render,
+    sextant::SextantCanvas,
+    sprite_asset::SpriteAsset,
+    sprite_registry::{SharedRegistry, SpriteRegistry},
     tank::Tank,
     terminal,
+    terminal_canvas::TerminalCanvas,
 };
 
 const CANVAS_MAX_X: usize = 120;
 const CANVAS_MAX_Y: usize = 80;
+const WORLD_MAX_X: usize = CANVAS_MAX_X * 3;
+const WORLD_MAX_Y: usize = CANVAS_MAX_Y * 2;
+// a smaller window shows this much of the world scaled down
+const MIN_VIEW: (usize, usize) = (CANVAS_MAX_X, CANVAS_MAX_Y);
+const CAMERA_DEAD_ZONE: IndexType = 30;
+// 5ms ticks, the shot timers are multiples of it
+const TICK_RATE: u32 = 200;
+const MAX_FPS: u32 = 60;
+const STATUS_ROWS: u16 = 2;
+const HELP_LINE: &str = " arrows: move  space: shoot  c: explode  q: quit";
+const GIF_DOT_SIZE: usize = 2;
+const TANK_ASSET: &str = "tank.sprite";
+
+// everything the render thread needs to show one frame
+struct Frame<C> {
+    canvas: C,
+    status: Vec<String>,
+    origin: (u16, u16),
+    // (columns, rows)
+    terminal_size: (u16, u16),
+    // repaint the whole screen, not just the difference
+    force: bool,
+}
+
+struct Options {
+    canvas: String,
+    gif: Option<PathBuf>,
+    cast: Option<PathBuf>,
+    assets: Option<PathBuf>,
+}
+
+fn arg_value(args: &[String], name: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == name)
+        .map(|id| args.get(id + 1).cloned().unwrap_or_default())
+}
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    let options = Options {
+        canvas: arg_value(&args, "--canvas").unwrap_or_else(|| "braille".to_string()),
+        gif: arg_value(&args, "--gif").map(PathBuf::from),
+        cast: arg_value(&args, "--cast").map(PathBuf::from),
+        assets: arg_value(&args, "--assets").map(PathBuf::from),
+    };
+    match options.canvas.as_str() {
+        "braille" => run::<BrailleCanvas>(options),
+        "halfblock" => run::<HalfBlockCanvas>(options),
+        "sextant" => run::<SextantCanvas>(options),
+        "ascii" => run::<AsciiCanvas>(options),
+        _ => eprintln!(
+            "unknown canvas '{}', use one of: braille, halfblock, sextant, ascii",
+            options.canvas
+        ),
+    }
+}
+
+/*
+    the built-in art is used when there is no assets directory.
+    the art of the file is registered once under the path, the next tanks share it.
+*/
+fn load_tank(
+    dir: Option<&PathBuf>,
+    registry: &SharedRegistry,
+    x: IndexType,
+    y: IndexType,
+) -> Result<Tank, String> {
+    let Some(dir) = dir else {
+        return Ok(Tank::new_with_registry(x, y, registry));
+    };
+    let path = dir.join(TANK_ASSET);
+    let kind = path.display().to_string();
+    if !Tank::has_kind(&registry.borrow(), &kind) {
+        let asset = SpriteAsset::load(&path).map_err(|err| format!("{}: {}", kind, err))?;
+        let animated = asset
+            .build("tank")
+            .ok_or_else(|| format!("{}: no 'tank' animation", kind))?;
+        Tank::register_kind(&mut registry.borrow_mut(), &kind, animated);
+    }
+    Ok(Tank::new_of_kind(x, y, &kind, registry))
+}
+
+fn run<C: TerminalCanvas + Send + 'static>(options: Options) {
+    let registry = SpriteRegistry::new_shared();
+    let tank_y = (CANVAS_MAX_Y - 15) as IndexType;
+    let mut tank = match load_tank(options.assets.as_ref(), &registry, 10, tank_y) {
+        Ok(tank) => tank,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
     let mut stdout = terminal::init().unwrap();
-    let mut instant = Instant::now();
+
+    let cell_dots = C::new(0, 0).get_cell_dots();
+    let world_size = (WORLD_MAX_X, WORLD_MAX_Y);
+    let mut terminal_size = terminal::size().unwrap();
+    let fit = |terminal| {
+        CanvasLayout::fit_scaled(terminal, STATUS_ROWS, cell_dots, world_size, MIN_VIEW)
+    };
+    let mut layout = fit(terminal_size);
+    let mut view = layout.get_view_size(world_size);
 
     // render
-    let (render_tx, render_rx) = mpsc::channel();
+    let (mut render_tx, render_rx) = frame_channel::<Frame<C>>();
     let thread_handle = thread::spawn(move || {
-        let mut last_canvas = BrailleCanvas::new(CANVAS_MAX_X, CANVAS_MAX_Y);
-        let mut stdout = io::stdout();
-        render::render(&mut stdout, &last_canvas, &last_canvas, true);
-        loop {
-            let cur_canvas = match render_rx.recv() {
-                Ok(value) => value,
-                Err(_) => break,
+        let mut cast = match options.cast.as_ref() {
+            Some(path) => {
+                let (width, height) = terminal_size;
+                let mut cast = CastWriter::new(
+                    BufWriter::new(io::stdout()),
+                    BufWriter::new(File::create(path)?),
+                    width as usize,
+                    height as usize,
+                )?;
+                // the terminal is set up already, only the recording misses it
+                let mut init = Vec::new();
+                terminal::write_init(&mut init)?;
+                cast.record(&init)?;
+                Some(cast)
+            }
+            None => None,
+        };
+        let mut plain = BufWriter::new(io::stdout());
+        let mut recorder = match options.gif.as_ref() {
+            Some(path) => Some(GifRecorder::create(path, GIF_DOT_SIZE)?),
+            None => None,
+        };
+        let started = Instant::now();
+        // the frame currently on the screen
+        let mut front: Option<Frame<C>> = None;
+        while let Some(frame) = render_rx.recv() {
+            let mut stdout: &mut dyn Write = match cast.as_mut() {
+                Some(cast) => {
+                    let (width, height) = frame.terminal_size;
+                    cast.resize(width as usize, height as usize)?;
+                    cast
+                }
+                None => &mut plain,
+            };
+            let force = frame.force || front.is_none();
+            let (last_canvas, last_status) = match front.as_ref() {
+                Some(f) => (&f.canvas, f.status.as_slice()),
+                None => (&frame.canvas, &[][..]),
             };
-            render::render(&mut stdout, &last_canvas, &cur_canvas, false);
-            last_canvas = cur_canvas;
+            // the canvas and the status lines go out as one flush, one cast event
+            render::queue_at(&mut stdout, last_canvas, &frame.canvas, frame.origin, force)?;
+            let (width, height) = frame.canvas.get_cells_size();
+            render::render_status(
+                &mut stdout,
+                (frame.origin.0, frame.origin.1 + height as u16),
+                width,
+                last_status,
+                &frame.status,
+                CellColor::new(Color::White, Color::DarkBlue),
+                force,
+            )?;
+            stdout.flush()?;
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.capture(&frame.canvas, started.elapsed());
+            }
+            if let Some(old) = front.replace(frame) {
+                render_rx.recycle(old);
+            }
         }
+        if let Some(recorder) = recorder {
+            recorder.finish()?;
+        }
+        Ok::<(), io::Error>(())
     });
 
-    let mut tank = Tank::new(10, (CANVAS_MAX_Y - 15 as usize) as IndexType);
+    tank.set_color(Color::Green);
     let mut obstacles = Obstacles::new();
     obstacles.add_obstacle(Obstacle::new_circle(10, 10, 10));
     obstacles.add_obstacle(Obstacle::new_rect(20, 0, 40, 20));
@@ -49,22 +222,39 @@ fn main() {
     obstacles.add_obstacle(Obstacle::new_frame(
         0,
         0,
-        CANVAS_MAX_X as IndexType - 1,
-        CANVAS_MAX_Y as IndexType - 1,
+        WORLD_MAX_X as IndexType - 1,
+        WORLD_MAX_Y as IndexType - 1,
     ));
     obstacles.get_all_mut()[0].set_solid(false);
     obstacles.get_all_mut()[1].set_solid(false);
+    obstacles.get_all_mut()[3].set_layer(Layer::Overhead); // foliage
     obstacles.get_all_mut()[4].set_ground(true);
     obstacles.get_all_mut()[5].set_visible(false); // frame
+    obstacles.add_obstacle(Obstacle::new_circle(180, 60, 15));
+    obstacles.add_obstacle(Obstacle::new_rect(140, 100, 220, 110));
+    obstacles.add_obstacle(Obstacle::new_ellipse(300, 40, 25, 12));
+    obstacles.add_obstacle(Obstacle::new_transparent_rect(250, 90, 320, 140, 2));
+    for o in obstacles.get_all_mut().iter_mut() {
+        o.set_color(Color::Grey);
+    }
 
-    'mainloop: loop {
-        let mut canvas = BrailleCanvas::new(CANVAS_MAX_X, CANVAS_MAX_Y);
-        let delta = instant.elapsed();
-        instant = Instant::now();
+    let mut camera = Camera::new(
+        view.0 as IndexType,
+        view.1 as IndexType,
+        WORLD_MAX_X as IndexType,
+        WORLD_MAX_Y as IndexType,
+    )
+    .with_dead_zone(CAMERA_DEAD_ZONE, CAMERA_DEAD_ZONE / 2);
+    camera.center_on(tank.get_pos());
+    let mut compositor = Compositor::new(view.0, view.1);
+    let mut force_redraw = true;
+
+    let mut game_loop = GameLoop::new(TICK_RATE, MAX_FPS);
 
+    'mainloop: loop {
         while event::poll(Duration::default()).unwrap() {
-            if let Event::Key(key_event) = event::read().unwrap() {
-                match key_event.code {
+            match event::read().unwrap() {
+                Event::Key(key_event) => match key_event.code {
                     KeyCode::Left => tank.go_left(),
                     KeyCode::Right => tank.go_right(),
                     KeyCode::Up => tank.go_up(),
@@ -75,22 +265,84 @@ fn main() {
                         break 'mainloop;
                     }
                     _ => {}
+                },
+                Event::Resize(cols, rows) => {
+                    terminal_size = (cols, rows);
+                    layout = fit((cols, rows));
+                    view = layout.get_view_size(world_size);
+                    camera.set_view_size(view.0 as IndexType, view.1 as IndexType);
+                    compositor = Compositor::new(view.0, view.1);
+                    force_redraw = true;
                 }
+                _ => {}
             }
         }
 
-        tank.update(delta);
-        obstacles.update(delta);
-        tank.check_obstacles(&mut obstacles);
-
-        obstacles.draw(&mut canvas);
-        tank.draw(&mut canvas);
+        game_loop.update(|tick| {
+            tank.update(tick);
+            obstacles.update(tick);
+            tank.check_obstacles(&mut obstacles);
+        });
 
-        render_tx.send(canvas).unwrap();
-        thread::sleep(Duration::from_millis(2));
+        if game_loop.should_render() {
+            let mut frame = render_tx.acquire(|| Frame {
+                canvas: C::new(layout.width, layout.height),
+                status: Vec::new(),
+                origin: layout.origin,
+                terminal_size,
+                force: true,
+            });
+            if frame.canvas.get_cells_size() == layout.get_cells_size(cell_dots) {
+                frame.canvas.clear();
+            } else {
+                frame.canvas = C::new(layout.width, layout.height);
+            }
+            camera.follow(tank.get_rect());
+            compositor.clear();
+            for o in obstacles.get_all() {
+                camera.draw_object(o, compositor.get_layer_mut(o.get_layer()));
+            }
+            camera.draw(&tank, compositor.get_layer_mut(tank.get_layer()));
+            compositor.get_layer_mut(Layer::Hud).draw_text(
+                Point::new(view.0 as IndexType - 2, 2),
+                &format!("SHOTS {}", tank.get_shots_count()),
+                &TextStyle::new(&FONT_3X5)
+                    .with_align(TextAlign::Right)
+                    .with_color(Color::White),
+            );
+            compositor.compose(&mut ScaledView::new(&mut frame.canvas, layout.scale));
+            let pos = tank.get_pos();
+            let status = vec![
+                format!(
+                    " tank {:>3},{:<3}  camera {:>3},{:<3}  fps {:>5.1}  tick {:>4}us",
+                    pos.x,
+                    pos.y,
+                    camera.get_pos().x,
+                    camera.get_pos().y,
+                    game_loop.get_fps(),
+                    game_loop.get_tick_time().as_micros()
+                ),
+                format!("{}  dropped {}", HELP_LINE, render_tx.get_dropped_count()),
+            ];
+            frame.status = status;
+            frame.origin = layout.origin;
+            frame.terminal_size = terminal_size;
+            frame.force = force_redraw;
+            match render_tx.submit(frame) {
+                Ok(true) => force_redraw = false,
+                // the renderer is behind, the frame is dropped
+                Ok(false) => {}
+                // the render thread is gone, most likely because of an output error
+                Err(_) => break 'mainloop,
+            }
+            game_loop.rendered();
+        }
+        game_loop.sleep();
     }
 
     drop(render_tx);
     terminal::deinit(&mut stdout).unwrap();
-    thread_handle.join().unwrap();
+    if let Err(err) = thread_handle.join().unwrap() {
+        eprintln!("render error: {}", err);
+    }
 }
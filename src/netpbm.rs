@@ -0,0 +1,358 @@
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use crate::{canvas::Canvas, position::IndexType, sprite::Sprite};
+
+/*
+    Netpbm images: P1 (pbm), P2 (pgm), P3 (ppm) as text and P4, P5, P6 as binary.
+    Every image is turned into gray levels, 0 is black and 255 is white.
+    Dark pixels become dots of the sprite.
+*/
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "{}", err),
+            ImageError::Format(message) => write!(f, "bad image: {}", message),
+        }
+    }
+}
+
+impl Error for ImageError {}
+
+impl From<io::Error> for ImageError {
+    fn from(err: io::Error) -> Self {
+        ImageError::Io(err)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Conversion {
+    // a dot for every pixel darker than the value
+    Threshold(u8),
+    // 4x4 Bayer matrix, keeps the gradients
+    Ordered,
+}
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, ImageError> {
+        let mut reader = Reader { data, pos: 0 };
+        let magic = reader.token()?;
+        let kind = match magic.as_slice() {
+            [b'P', kind @ b'1'..=b'6'] => kind - b'0',
+            _ => return Err(format_error("not a netpbm image")),
+        };
+        let width = reader.number()?;
+        let height = reader.number()?;
+        let max_value = if kind == 1 || kind == 4 {
+            1
+        } else {
+            reader.number()?
+        };
+        if width == 0 || height == 0 {
+            return Err(format_error("empty image"));
+        }
+        // sprites are indexed with IndexType
+        if width > IndexType::MAX as usize || height > IndexType::MAX as usize {
+            return Err(too_big());
+        }
+        if max_value == 0 || max_value > 65535 {
+            return Err(format_error("bad maximum value"));
+        }
+
+        let count = width.checked_mul(height).ok_or_else(too_big)?;
+        let channels = if kind == 3 || kind == 6 { 3 } else { 1 };
+        let sample_size = if max_value > 255 { 2 } else { 1 };
+        let row_bytes = width.div_ceil(8);
+        // the least data the pixels take, checked before anything is allocated
+        let needed = match kind {
+            1..=3 => count.checked_mul(channels),
+            4 => row_bytes.checked_mul(height),
+            _ => count
+                .checked_mul(channels)
+                .and_then(|c| c.checked_mul(sample_size)),
+        }
+        .ok_or_else(too_big)?;
+        if reader.left() < needed {
+            return Err(format_error("unexpected end of data"));
+        }
+        let mut pixels = Vec::with_capacity(count);
+        match kind {
+            1 => {
+                for _ in 0..count {
+                    // the bits may go without spaces between them
+                    pixels.push(if reader.bit()? { 0 } else { 255 });
+                }
+            }
+            2 | 3 => {
+                for _ in 0..count {
+                    let rgb = if kind == 3 {
+                        [reader.number()?, reader.number()?, reader.number()?]
+                    } else {
+                        let v = reader.number()?;
+                        [v, v, v]
+                    };
+                    pixels.push(to_gray(rgb, max_value)?);
+                }
+            }
+            4 => {
+                reader.skip_one_space();
+                let rows = reader.bytes(needed)?;
+                for y in 0..height {
+                    for x in 0..width {
+                        let byte = rows[y * row_bytes + x / 8];
+                        pixels.push(if byte & (0x80 >> (x % 8)) != 0 { 0 } else { 255 });
+                    }
+                }
+            }
+            _ => {
+                reader.skip_one_space();
+                let samples = reader.bytes(needed)?;
+                let sample = |id: usize| {
+                    if sample_size == 2 {
+                        (samples[id * 2] as usize) << 8 | samples[id * 2 + 1] as usize
+                    } else {
+                        samples[id] as usize
+                    }
+                };
+                for id in 0..count {
+                    let rgb = if channels == 3 {
+                        [sample(id * 3), sample(id * 3 + 1), sample(id * 3 + 2)]
+                    } else {
+                        [sample(id), sample(id), sample(id)]
+                    };
+                    pixels.push(to_gray(rgb, max_value)?);
+                }
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_gray(&self, x: usize, y: usize) -> Option<u8> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn to_sprite(&self, conversion: Conversion) -> Sprite {
+        self.to_sprite_region(0, 0, self.width, self.height, conversion)
+    }
+
+    // the sprite keeps the size of the region even if its edges are empty,
+    // a region too big for IndexType is cut to it
+    pub fn to_sprite_region(
+        &self,
+        left: usize,
+        top: usize,
+        width: usize,
+        height: usize,
+        conversion: Conversion,
+    ) -> Sprite {
+        let width = width.min(IndexType::MAX as usize);
+        let height = height.min(IndexType::MAX as usize);
+        let mut sprite = Sprite::new_with_size(width as IndexType, height as IndexType);
+        for y in 0..height {
+            for x in 0..width {
+                let Some(gray) = self.get_gray(left.saturating_add(x), top.saturating_add(y)) else {
+                    continue;
+                };
+                // the matrix follows the image so neighbouring frames match
+                let limit = match conversion {
+                    Conversion::Threshold(value) => value,
+                    Conversion::Ordered => BAYER_4X4[(top + y) % 4][(left + x) % 4] * 16 + 8,
+                };
+                if gray < limit {
+                    sprite.draw_dot(x as IndexType, y as IndexType);
+                }
+            }
+        }
+        sprite
+    }
+
+    // cuts a sprite sheet into cells, row by row from the top left one
+    pub fn split(&self, cell_width: usize, cell_height: usize, conversion: Conversion) -> Vec<Sprite> {
+        let mut sprites = Vec::new();
+        if cell_width == 0 || cell_height == 0 {
+            return sprites;
+        }
+        for row in 0..self.height / cell_height {
+            for column in 0..self.width / cell_width {
+                sprites.push(self.to_sprite_region(
+                    column * cell_width,
+                    row * cell_height,
+                    cell_width,
+                    cell_height,
+                    conversion,
+                ));
+            }
+        }
+        sprites
+    }
+}
+
+fn format_error(message: &str) -> ImageError {
+    ImageError::Format(message.to_string())
+}
+
+fn too_big() -> ImageError {
+    format_error("image is too big")
+}
+
+fn to_gray(rgb: [usize; 3], max_value: usize) -> Result<u8, ImageError> {
+    if rgb.iter().any(|v| *v > max_value) {
+        return Err(format_error("sample is above the maximum value"));
+    }
+    let luma = (rgb[0] * 299 + rgb[1] * 587 + rgb[2] * 114) / 1000;
+    Ok((luma * 255 / max_value) as u8)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    // whitespace and the comments of the text parts
+    fn skip_spaces(&mut self) {
+        while let Some(c) = self.data.get(self.pos) {
+            if *c == b'#' {
+                while self.data.get(self.pos).is_some_and(|c| *c != b'\n') {
+                    self.pos += 1;
+                }
+            } else if c.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    // the binary data starts right after a single whitespace
+    fn skip_one_space(&mut self) {
+        if self.data.get(self.pos).is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn token(&mut self) -> Result<Vec<u8>, ImageError> {
+        self.skip_spaces();
+        let start = self.pos;
+        while self
+            .data
+            .get(self.pos)
+            .is_some_and(|c| !c.is_ascii_whitespace() && *c != b'#')
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(format_error("unexpected end of data"));
+        }
+        Ok(self.data[start..self.pos].to_vec())
+    }
+
+    fn number(&mut self) -> Result<usize, ImageError> {
+        let token = self.token()?;
+        std::str::from_utf8(&token)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| format_error("expected a number"))
+    }
+
+    fn bit(&mut self) -> Result<bool, ImageError> {
+        self.skip_spaces();
+        let bit = match self.data.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            Some(_) => return Err(format_error("expected 0 or 1")),
+            None => return Err(format_error("unexpected end of data")),
+        };
+        self.pos += 1;
+        Ok(bit)
+    }
+
+    fn left(&self) -> usize {
+        self.data.len().saturating_sub(self.pos)
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&[u8], ImageError> {
+        let bytes = self
+            .pos
+            .checked_add(count)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| format_error("unexpected end of data"))?;
+        self.pos += count;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_format_error(result: Result<Image, ImageError>) -> bool {
+        matches!(result, Err(ImageError::Format(_)))
+    }
+
+    #[test]
+    fn small_images() {
+        let image = Image::parse(b"P1 2 2 1 0 0 1").unwrap();
+        assert_eq!(image.get_gray(0, 0), Some(0));
+        assert_eq!(image.get_gray(1, 0), Some(255));
+        let image = Image::parse(b"P5 2 1 255\n\x00\xff").unwrap();
+        assert_eq!(image.get_gray(1, 0), Some(255));
+    }
+
+    #[test]
+    fn sizes_that_overflow_are_errors() {
+        let huge = usize::MAX.to_string();
+        for kind in ["P1", "P2", "P3", "P4", "P5", "P6"] {
+            let header = format!("{} {} {} 255\n", kind, huge, huge);
+            assert!(is_format_error(Image::parse(header.as_bytes())));
+        }
+    }
+
+    #[test]
+    fn sizes_above_index_type_are_errors() {
+        let header = format!("P5 {} 1 255\n", IndexType::MAX as usize + 1);
+        assert!(is_format_error(Image::parse(header.as_bytes())));
+    }
+
+    #[test]
+    fn big_sizes_need_the_data_first() {
+        // nothing is allocated for the pixels that are not there
+        assert!(is_format_error(Image::parse(b"P6 32767 32767 65535\n\x00")));
+        assert!(is_format_error(Image::parse(b"P2 32767 32767 255 1 2 3")));
+        assert!(is_format_error(Image::parse(b"P4 32767 32767\n\x00")));
+    }
+}
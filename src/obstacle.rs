@@ -1,9 +1,11 @@
-use std::{collections::HashSet, time::Duration};
+use std::time::Duration;
 
 use crate::{
     canvas::Canvas,
+    color::Color,
     drawable::Drawable,
     game_object::game_object::GameObject,
+    layer::Layer,
     position::{AsPoint, IndexType, Point},
     sprite::Sprite, 
     game_object::{GameObjectStatic, GameObjectArea},
@@ -14,8 +16,7 @@ pub struct Obstacle {
     transparent: bool,
     solid: bool,
     visible: bool,
-    ground: bool,
-    invisible: HashSet<Point>,
+    layer: Layer,
 }
 
 impl Obstacle {
@@ -27,25 +28,21 @@ impl Obstacle {
             transparent: false,
             solid: true,
             visible: true,
-            ground: false,
-            invisible: HashSet::new(),
+            layer: Layer::Objects,
         }
     }
 
     pub fn new_rect(x1: IndexType, y1: IndexType, x2: IndexType, y2: IndexType) -> Self {
         let mut s = Sprite::new();
-        for x in 0..x2 - x1 {
-            for y in 0..y2 - y1 {
-                s.draw_dot(x, y);
-            }
+        if x2 > x1 && y2 > y1 {
+            s.fill_rectangle((0, 0).as_point(), (x2 - x1 - 1, y2 - y1 - 1).as_point());
         }
         Self {
             area: GameObjectStatic::new(s, x1, y1),
             transparent: false,
             solid: true,
             visible: true,
-            ground: false,
-            invisible: HashSet::new(),
+            layer: Layer::Objects,
         }
     }
 
@@ -69,23 +66,55 @@ impl Obstacle {
             transparent: true,
             solid: false,
             visible: true,
-            ground: false,
-            invisible: HashSet::new(),
+            layer: Layer::Objects,
         }
     }
 
     pub fn new_circle(x1: IndexType, y1: IndexType, radius: IndexType) -> Self {
         let mut s = Sprite::new();
-        for r in 1..=radius {
-            s.draw_circle((x1, y1).as_point(), r);
-        }
+        s.fill_circle((radius, radius).as_point(), radius);
         Self {
             area: GameObjectStatic::new(s, x1 - radius, y1 - radius),
             transparent: false,
             solid: true,
             visible: true,
-            ground: false,
-            invisible: HashSet::new(),
+            layer: Layer::Objects,
+        }
+    }
+
+    pub fn new_ellipse(
+        x1: IndexType,
+        y1: IndexType,
+        radius_x: IndexType,
+        radius_y: IndexType,
+    ) -> Self {
+        let mut s = Sprite::new();
+        s.fill_ellipse((radius_x, radius_y).as_point(), radius_x, radius_y);
+        Self {
+            area: GameObjectStatic::new(s, x1 - radius_x, y1 - radius_y),
+            transparent: false,
+            solid: true,
+            visible: true,
+            layer: Layer::Objects,
+        }
+    }
+
+    // points are in world coordinates, the obstacle is placed at their bounding box
+    pub fn new_polygon(points: &[Point]) -> Self {
+        let min_x = points.iter().map(|p| p.x).min().unwrap_or(0);
+        let min_y = points.iter().map(|p| p.y).min().unwrap_or(0);
+        let local: Vec<Point> = points
+            .iter()
+            .map(|p| (p.x - min_x, p.y - min_y).as_point())
+            .collect();
+        let mut s = Sprite::new();
+        s.fill_polygon(&local);
+        Self {
+            area: GameObjectStatic::new(s, min_x, min_y),
+            transparent: false,
+            solid: true,
+            visible: true,
+            layer: Layer::Objects,
         }
     }
 
@@ -94,7 +123,11 @@ impl Obstacle {
     }
 
     pub fn set_ground(&mut self, value: bool) {
-        self.ground = value;
+        self.layer = if value { Layer::Ground } else { Layer::Objects };
+    }
+
+    pub fn set_layer(&mut self, layer: Layer) {
+        self.layer = layer;
     }
 
     pub fn set_solid(&mut self, value: bool) {
@@ -105,14 +138,20 @@ impl Obstacle {
         self.visible = value;
     }
 
-    pub fn update(&mut self, _: Duration) {
-        self.invisible.clear();
+    pub fn set_color(&mut self, color: Color) {
+        self.area.set_color(Some(color));
     }
 
+    pub fn update(&mut self, _: Duration) {}
+
     pub fn is_done(&self) -> bool {
         self.area.sprite.is_empty()
     }
 
+    pub fn get_sprite(&self) -> &Sprite {
+        &self.area.sprite
+    }
+
     pub fn clean(&mut self, dot: &Point) {
         self.area.sprite.clean(dot);
     }
@@ -130,11 +169,7 @@ impl Obstacle {
     }
 
     pub fn is_ground(&self) -> bool {
-        self.ground
-    }
-
-    pub fn set_invisivle_dots(&mut self, val: HashSet<Point>) {
-        self.invisible = val;
+        self.layer == Layer::Ground
     }
 }
 
@@ -167,16 +202,22 @@ impl Obstacles {
     }
 }
 
+impl Default for Obstacles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drawable for Obstacle {
     fn draw(&self, canvas: &mut dyn Canvas) {
         if self.visible {
             self.area.draw_to_canvas(canvas);
-            let area_pos = self.area.get_pos();
-            for p in self.invisible.iter() {
-                canvas.clean_dot(p.x + area_pos.x, p.y + area_pos.y);
-            }
         }
     }
+
+    fn get_layer(&self) -> Layer {
+        self.layer
+    }
 }
 
 impl Drawable for Obstacles {
@@ -1,8 +1,20 @@
 use std::collections::HashSet;
-use crate::position::Point;
+use crate::{bit_matrix::BitMatrix, position::Point};
 
 pub trait PointSet {
     fn get_point_set(&self) -> Option<&HashSet<Point>>;
+    // the same dots as bits, collisions and drawing use it when it is there
+    fn get_bits(&self) -> Option<&BitMatrix> {
+        None
+    }
     fn get_max(&self) -> Point;
     fn is_empty(&self) -> bool;
+    // the dots to draw, the same as the ones above unless the set shows more than it is
+    fn for_each_drawn(&self, call: &mut dyn FnMut(Point)) {
+        if let Some(bits) = self.get_bits() {
+            bits.iter().for_each(call);
+        } else if let Some(points) = self.get_point_set() {
+            points.iter().copied().for_each(call);
+        }
+    }
 }
@@ -1,30 +1,128 @@
-use crate::{braille, braille_canvas::BrailleCanvas};
+use crate::{color::CellColor, terminal_canvas::TerminalCanvas};
 use crossterm::{
     cursor::MoveTo,
-    style::{Color, SetBackgroundColor},
+    style::{Color, Print, SetBackgroundColor, SetForegroundColor},
     terminal::{Clear, ClearType},
     QueueableCommand,
 };
-use std::io::{Stdout, Write};
+use std::io::{self, Write};
 
-pub fn render(
-    stdout: &mut Stdout,
-    last_frame: &BrailleCanvas,
-    current_frame: &BrailleCanvas,
+fn cell_changed<C: TerminalCanvas>(last_frame: &C, current_frame: &C, x: usize, y: usize) -> bool {
+    let (width, height) = last_frame.get_cells_size();
+    x >= width
+        || y >= height
+        || current_frame.get_cell_char(x, y) != last_frame.get_cell_char(x, y)
+        || current_frame.get_cell_color(x, y) != last_frame.get_cell_color(x, y)
+}
+
+/*
+    writes the difference between two frames to `out`.
+    changed cells that are next to each other on a row are sent as one run:
+    a single MoveTo followed by the text, colors are switched only when they change.
+*/
+pub fn render<W: Write, C: TerminalCanvas>(
+    out: &mut W,
+    last_frame: &C,
+    current_frame: &C,
+    force: bool,
+) -> io::Result<()> {
+    render_at(out, last_frame, current_frame, (0, 0), force)
+}
+
+// same as `render` with the top left cell of the canvas at `origin` (column, row)
+pub fn render_at<W: Write, C: TerminalCanvas>(
+    out: &mut W,
+    last_frame: &C,
+    current_frame: &C,
+    origin: (u16, u16),
     force: bool,
-) {
+) -> io::Result<()> {
+    queue_at(out, last_frame, current_frame, origin, force)?;
+    out.flush()
+}
+
+// `render_at` without the flush, so more can go into the same frame
+pub fn queue_at<W: Write, C: TerminalCanvas>(
+    out: &mut W,
+    last_frame: &C,
+    current_frame: &C,
+    origin: (u16, u16),
+    force: bool,
+) -> io::Result<()> {
     if force {
-        stdout.queue(SetBackgroundColor(Color::Blue)).unwrap();
-        stdout.queue(Clear(ClearType::All)).unwrap();
-        stdout.queue(SetBackgroundColor(Color::Black)).unwrap();
+        out.queue(SetBackgroundColor(Color::Blue))?;
+        out.queue(Clear(ClearType::All))?;
+        out.queue(SetBackgroundColor(Color::Black))?;
     }
-    for (x, col) in current_frame.area().iter().enumerate() {
-        for (y, s) in col.iter().enumerate() {
-            if *s != last_frame.area()[x][y] || force {
-                stdout.queue(MoveTo(x as u16, y as u16)).unwrap();
-                print!("{}", braille::to_char(*s));
+    // colors currently set on the terminal, None if unknown
+    let mut active: Option<CellColor> = None;
+    let mut run = String::new();
+    let (width, height) = current_frame.get_cells_size();
+    for y in 0..height {
+        let mut x = 0;
+        while x < width {
+            if !force && !cell_changed(last_frame, current_frame, x, y) {
+                x += 1;
+                continue;
+            }
+            out.queue(MoveTo(origin.0 + x as u16, origin.1 + y as u16))?;
+            while x < width && (force || cell_changed(last_frame, current_frame, x, y)) {
+                let color = current_frame.get_cell_color(x, y);
+                if active != Some(color) {
+                    flush_run(out, &mut run)?;
+                    if active.is_none_or(|a| a.fg != color.fg) {
+                        out.queue(SetForegroundColor(color.fg))?;
+                    }
+                    if active.is_none_or(|a| a.bg != color.bg) {
+                        out.queue(SetBackgroundColor(color.bg))?;
+                    }
+                    active = Some(color);
+                }
+                run.push(current_frame.get_cell_char(x, y));
+                x += 1;
             }
+            flush_run(out, &mut run)?;
+        }
+    }
+    Ok(())
+}
+
+fn flush_run<W: Write>(out: &mut W, run: &mut String) -> io::Result<()> {
+    if !run.is_empty() {
+        out.queue(Print(run.as_str()))?;
+        run.clear();
+    }
+    Ok(())
+}
+
+/*
+    plain terminal text in the rows reserved below the canvas,
+    `origin` is the first column and row of the status area.
+    every line is padded or cut to `width` columns and redrawn only when it changes.
+    nothing is flushed, the caller flushes once for the whole frame.
+*/
+pub fn render_status<W: Write>(
+    out: &mut W,
+    origin: (u16, u16),
+    width: usize,
+    last_lines: &[String],
+    current_lines: &[String],
+    color: CellColor,
+    force: bool,
+) -> io::Result<()> {
+    let mut colors_set = false;
+    for (id, line) in current_lines.iter().enumerate() {
+        if !force && last_lines.get(id) == Some(line) {
+            continue;
+        }
+        if !colors_set {
+            out.queue(SetForegroundColor(color.fg))?;
+            out.queue(SetBackgroundColor(color.bg))?;
+            colors_set = true;
         }
+        let text: String = line.chars().take(width).collect();
+        out.queue(MoveTo(origin.0, origin.1 + id as u16))?;
+        out.queue(Print(format!("{:<width$}", text, width = width)))?;
     }
-    stdout.flush().unwrap();
+    Ok(())
 }
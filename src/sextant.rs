@@ -0,0 +1,41 @@
+use crate::glyph_canvas::{GlyphCanvas, Glyphs};
+
+// two columns, three rows of dots per cell
+pub struct Sextant;
+
+const BASE: u32 = 0x1FB00;
+const LEFT_HALF: u8 = 0b010101;
+const RIGHT_HALF: u8 = 0b101010;
+const FULL: u8 = 0b111111;
+
+impl Glyphs for Sextant {
+    const WIDTH: usize = 2;
+    const HEIGHT: usize = 3;
+
+    /*
+        U+1FB00.. holds all the masks in order, except the ones
+        that already exist in the block elements range:
+        empty, left half, right half and full block.
+    */
+    fn to_char(mask: u8) -> char {
+        let mask = mask & FULL;
+        match mask {
+            0 => ' ',
+            LEFT_HALF => '\u{258C}',
+            RIGHT_HALF => '\u{2590}',
+            FULL => '\u{2588}',
+            _ => {
+                let mut id = mask as u32 - 1;
+                if mask > LEFT_HALF {
+                    id -= 1;
+                }
+                if mask > RIGHT_HALF {
+                    id -= 1;
+                }
+                char::from_u32(BASE + id).unwrap_or('?')
+            }
+        }
+    }
+}
+
+pub type SextantCanvas = GlyphCanvas<Sextant>;
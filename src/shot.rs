@@ -1,21 +1,26 @@
 use std::time::Duration;
 
 use crate::{
+    animated::Animated,
     animation_builder::AnimationBuilder,
+    animation_controller::{AnimationController, AnimationState},
     canvas::Canvas,
+    color::Color,
     drawable::Drawable,
     position::{AsPoint, IndexType, Point},
+    sprite_parser::DslParser,
+    sprite_registry::SpriteRegistry,
     timer::Timer, direction::Direction, 
-    game_object::{GameObjectArea, GameObjectAnimated, GameObject},
+    game_object::{GameObjectArea, GameObjectControlled, GameObject},
 };
 
-const BULLET_SPRITE: &'static str = r#"
+const BULLET_SPRITE: &str = r#"
   +x
   |X
   |x
 "#;
 
-const BULLET_EXPLODE: &'static str = r#"
+const BULLET_EXPLODE: &str = r#"
   +XX
   |XX
 -  
@@ -53,31 +58,70 @@ const BULLET_EXPLODE: &'static str = r#"
   | X X
 "#;
 
+const SHOT_COLOR: Color = Color::Yellow;
+// 7 frames of the explosion, about half a second
+const EXPLODE_FRAME_DELAY: Duration = Duration::from_millis(70);
+
+const BULLET_ASSET: &str = "shot/bullet";
+const EXPLODE_ASSET: &str = "shot/explode";
+
+// the explosion grows, its frames differ in size
+fn bullet_parser() -> DslParser {
+    DslParser::new().with_ragged_rows(true).with_mixed_sizes(true)
+}
+
 pub struct Shot {
-    area: GameObjectAnimated,
+    area: GameObjectControlled,
     explode_pos: Point,
     direction: Direction,
     delay: Timer,
-    exploding: bool,
 }
 
 impl Shot {
-    pub fn new(x: IndexType, y: IndexType, dir: Direction) -> Self {
-        let s = AnimationBuilder::new_static()
-                .add_sprite_from_string(BULLET_SPRITE)
-                .modify(|mut a| {
-                    if dir == Direction::Left || dir == Direction::Right {
-                        a.rotate_90();
-                    }
-                    a
-                })
-                .build();
+    /*
+        The controller every shot starts from, made once by the shooter.
+        The sprites are parsed the first time, the next templates share them.
+    */
+    pub fn template(assets: &mut SpriteRegistry) -> AnimationController {
+        let bullet = assets.get_or_insert_with(BULLET_ASSET, Direction::Up, || {
+            AnimationBuilder::new_static()
+                .try_add_from_string(BULLET_SPRITE, &bullet_parser())
+                .expect("the built-in bullet sprite is broken")
+                .build()
+        });
+        let explode = assets.get_or_insert_with(EXPLODE_ASSET, Direction::Up, || {
+            AnimationBuilder::new(EXPLODE_FRAME_DELAY)
+                .try_add_from_string(BULLET_EXPLODE, &bullet_parser())
+                .expect("the built-in explosion sprite is broken")
+                .build()
+        });
+        // flies until it explodes once, then there is nothing left
+        AnimationController::new(AnimationState::Move, bullet)
+            .with_state(AnimationState::Explode, explode)
+            .with_state(AnimationState::Destroyed, Animated::new_static())
+            .with_transition(AnimationState::Move, AnimationState::Explode)
+            .with_next(AnimationState::Explode, AnimationState::Destroyed)
+    }
+
+    // the template shares its frames and rules, only the bullet is turned to `dir`
+    pub fn new(
+        x: IndexType,
+        y: IndexType,
+        dir: Direction,
+        template: &AnimationController,
+        assets: &SpriteRegistry,
+    ) -> Self {
+        let mut controller = template.clone();
+        if let Some(bullet) = assets.get(BULLET_ASSET, dir) {
+            controller = controller.with_state(AnimationState::Move, bullet);
+        }
+        let mut area = GameObjectControlled::new(controller, x, y);
+        area.set_color(Some(SHOT_COLOR));
         Self {
-            area: GameObjectAnimated::new(s, x, y),
+            area,
             explode_pos: (x, y).as_point(),
             direction: dir,
             delay: Timer::new(Duration::from_millis(15)),
-            exploding: false,
         }
     }
 
@@ -88,38 +132,35 @@ impl Shot {
 
     pub fn update(&mut self, delta: Duration) {
         self.area.sprite.update(delta);
-        if self.delay.update(delta) {
-            if !self.exploding {
-                self.forward();
-                self.delay.reset();
-            }
+        if self.delay.update(delta) && self.is_flying() {
+            self.forward();
+            self.delay.reset();
         }
-        if self.exploding {
+        if !self.is_flying() {
             self.fix_explode_pos();
-        } 
+        }
     }
 
     pub fn explode(&mut self) -> bool {
-        if !self.exploding {
-            self.explode_pos = self.area.get_center_pos();
-            self.exploding = true;
-            self.delay = Timer::from_millis(500);
-            let explode = AnimationBuilder::new_looped(Duration::from_millis(100))
-                .add_from_string(BULLET_EXPLODE)
-                .build();
-            self.area.set_point_set(explode); 
+        let center = self.area.get_center_pos();
+        if self.area.sprite.request(AnimationState::Explode) {
+            self.explode_pos = center;
             self.fix_explode_pos();
             return true;
         }
         false
     }
 
+    fn is_flying(&self) -> bool {
+        self.area.sprite.get_state() == AnimationState::Move
+    }
+
     fn fix_explode_pos(&mut self) {
         self.area.move_center_to(self.explode_pos.x, self.explode_pos.y)
     }
 
     pub fn is_done(&self) -> bool {
-        self.exploding && self.delay.ready()
+        self.area.sprite.get_state() == AnimationState::Destroyed
     }
 
 }
@@ -0,0 +1,129 @@
+use std::{env, fs, path::Path};
+
+use crate::{
+    braille,
+    canvas::Canvas,
+    headless_canvas::HeadlessCanvas,
+    position::IndexType,
+    terminal_canvas::TerminalCanvas,
+};
+
+pub const DOT: char = '#';
+pub const EMPTY: char = '.';
+
+// set it to rewrite golden files instead of comparing with them
+pub const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+// one line per terminal row, exactly what the renderer would print
+pub fn to_cells_string<C: TerminalCanvas>(canvas: &C) -> String {
+    let (width, height) = canvas.get_cells_size();
+    let mut res = String::with_capacity((width + 1) * height);
+    for y in 0..height {
+        for x in 0..width {
+            res.push(canvas.get_cell_char(x, y));
+        }
+        res.push('\n');
+    }
+    res
+}
+
+// '#' for set dots, '.' for empty ones; needs `Canvas::get_dot`
+pub fn to_dot_string(canvas: &dyn Canvas, width: usize, height: usize) -> String {
+    let mut res = String::with_capacity((width + 1) * height);
+    for y in 0..height {
+        for x in 0..width {
+            let set = canvas.get_dot(x as IndexType, y as IndexType) == Some(true);
+            res.push(if set { DOT } else { EMPTY });
+        }
+        res.push('\n');
+    }
+    res
+}
+
+pub fn headless_to_string(canvas: &HeadlessCanvas) -> String {
+    to_dot_string(canvas, canvas.get_width(), canvas.get_height())
+}
+
+// the reverse of `to_dot_string`: '#' becomes a dot, anything else is empty
+pub fn draw_dot_string(canvas: &mut dyn Canvas, value: &str) {
+    for (y, line) in value.lines().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            if c == DOT {
+                canvas.draw_dot(x as IndexType, y as IndexType);
+            }
+        }
+    }
+}
+
+// the reverse of `to_cells_string` for braille lines, non braille chars are skipped
+pub fn draw_braille_string(canvas: &mut dyn Canvas, value: &str) {
+    for (y, line) in value.lines().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            if let Some(mask) = braille::from_char(c) {
+                for dx in 0..2 {
+                    for dy in 0..4 {
+                        if braille::get_dot(mask, dx, dy) {
+                            canvas.draw_dot((x * 2 + dx) as IndexType, (y * 4 + dy) as IndexType);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn headless_from_dot_string(value: &str) -> HeadlessCanvas {
+    let width = value.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut res = HeadlessCanvas::new(width, value.lines().count());
+    draw_dot_string(&mut res, value);
+    res
+}
+
+/*
+    compares `actual` with the golden file at `path`.
+    set UPDATE_SNAPSHOTS=1 to write new golden files or rewrite existing ones,
+    a missing file is a failure otherwise, so a wrong path can not pass.
+    panics with the first differing line, so it works as a test assertion.
+*/
+pub fn assert_snapshot<P: AsRef<Path>>(actual: &str, path: P) {
+    let path = path.as_ref();
+    if env::var_os(UPDATE_ENV).is_some() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(path, actual).unwrap();
+        return;
+    }
+    if !path.exists() {
+        panic!(
+            "snapshot {} is missing, run with {}=1 to create it\n\nactual frame:\n{}",
+            path.display(),
+            UPDATE_ENV,
+            actual
+        );
+    }
+    let expected = fs::read_to_string(path).unwrap().replace("\r\n", "\n");
+    if expected == actual {
+        return;
+    }
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => break,
+            (e, a) if e != a => {
+                panic!(
+                    "snapshot {} differs at line {}\nexpected: {}\n  actual: {}\n\nfull frame:\n{}",
+                    path.display(),
+                    line,
+                    e.unwrap_or("<none>"),
+                    a.unwrap_or("<none>"),
+                    actual
+                );
+            }
+            _ => line += 1,
+        }
+    }
+    panic!("snapshot {} differs in trailing whitespace", path.display());
+}
@@ -1,39 +1,247 @@
 use crate::{
+    bit_matrix::BitMatrix,
     canvas::Canvas,
     position::{AsPoint, IndexType, Point},
     point_set::PointSet
 };
-use std::{cmp::max, collections::HashSet};
+use std::{
+    cell::OnceCell,
+    cmp::max,
+    collections::{BTreeMap, HashSet},
+};
 
+/*
+    Dots from (0, 0) to max, stored as bits.
+    Dots left or above of (0, 0) are not kept any more, draw_dot rejects them and returns false,
+    union and xor move the dots instead.
+    The point set is built on the first get_point_set and kept in a OnceCell,
+    so a sprite can be sent to another thread but not shared between threads (it is not Sync).
+*/
+#[derive(Clone)]
 pub struct Sprite {
-    values: HashSet<Point>,
+    values: BitMatrix,
+    // the dots as a set, built on demand for PointSet::get_point_set
+    points: OnceCell<HashSet<Point>>,
     max: Point,
+    // named points like the pivot or the muzzle, they follow the transforms
+    anchors: BTreeMap<String, Point>,
 }
 
+// the point the sprite turns around
+pub const ANCHOR_PIVOT: &str = "pivot";
+// where the shots come out
+pub const ANCHOR_MUZZLE: &str = "muzzle";
+// the dot character of the written sprites
+pub const DSL_DOT: char = '*';
+
 impl Sprite {
     pub fn new() -> Self {
         Self {
-            values: HashSet::new(),
+            values: BitMatrix::default(),
+            points: OnceCell::new(),
             max: Point::new(0, 0),
+            anchors: BTreeMap::new(),
+        }
+    }
+
+    // an empty sprite that already covers width x height dots
+    pub fn new_with_size(width: IndexType, height: IndexType) -> Self {
+        Self {
+            values: BitMatrix::new(max(width, 0) as usize, max(height, 0) as usize),
+            points: OnceCell::new(),
+            max: Point::new(max(width - 1, 0), max(height - 1, 0)),
+            anchors: BTreeMap::new(),
         }
     }
 
     pub fn new_from_string(value: &str) -> Self {
-        let mut instance = Self {
-            values: HashSet::new(),
-            max: Point::new(0, 0),
-        };
+        let mut instance = Self::new();
         instance.draw_from_string(value);
         instance
     }
 
+    /*
+        Writes the sprite in the language of Canvas::draw_from_string,
+        '+' starts the first row and '|' every next one, the trailing spaces are dropped.
+        Every row is written, only the empty columns at the right edge are lost.
+    */
+    pub fn to_dsl_string(&self) -> String {
+        let mut res = String::new();
+        for y in 0..=self.max.y {
+            res.push(if y == 0 { '+' } else { '|' });
+            let row: String = (0..=self.max.x)
+                .map(|x| if self.values.get(x, y) { DSL_DOT } else { ' ' })
+                .collect();
+            res.push_str(row.trim_end());
+            res.push('\n');
+        }
+        res
+    }
+
+    pub fn set_anchor(&mut self, name: &str, point: Point) {
+        self.anchors.insert(name.to_string(), point);
+    }
+
+    pub fn get_anchor(&self, name: &str) -> Option<Point> {
+        self.anchors.get(name).copied()
+    }
+
+    pub fn get_anchors(&self) -> impl Iterator<Item = (&str, Point)> {
+        self.anchors.iter().map(|(name, p)| (name.as_str(), *p))
+    }
+
+    /*
+        The transforms keep the sprite inside (0, 0)..=max,
+        max is recalculated for the new shape.
+        The anchors are moved the same way as the dots.
+    */
+
+    // clockwise
     pub fn rotate_90(&mut self) {
+        let max = self.max;
+        self.transform(Point::new(max.y, max.x), |p| Point::new(max.y - p.y, p.x));
+    }
+
+    pub fn rotate_180(&mut self) {
+        let max = self.max;
+        self.transform(max, |p| Point::new(max.x - p.x, max.y - p.y));
+    }
+
+    pub fn rotate_270(&mut self) {
+        let max = self.max;
+        self.transform(Point::new(max.y, max.x), |p| Point::new(p.y, max.x - p.x));
+    }
+
+    // clockwise quarter turns, negative values turn counterclockwise
+    pub fn rotate_quarters(&mut self, turns: i32) {
+        match turns.rem_euclid(4) {
+            1 => self.rotate_90(),
+            2 => self.rotate_180(),
+            3 => self.rotate_270(),
+            _ => {}
+        }
+    }
+
+    // mirrors left to right
+    pub fn flip_horizontal(&mut self) {
+        let max = self.max;
+        self.transform(max, |p| Point::new(max.x - p.x, p.y));
+    }
+
+    // mirrors top to bottom
+    pub fn flip_vertical(&mut self) {
+        let max = self.max;
+        self.transform(max, |p| Point::new(p.x, max.y - p.y));
+    }
+
+    // every dot becomes a factor x factor block
+    pub fn scale_up(&mut self, factor: IndexType) {
+        if factor <= 1 {
+            return;
+        }
+        let max = self.max;
+        let mut tmp = Self::new_with_size((max.x + 1) * factor, (max.y + 1) * factor);
+        for p in self.values.iter() {
+            tmp.fill_rectangle(
+                Point::new(p.x * factor, p.y * factor),
+                Point::new(p.x * factor + factor - 1, p.y * factor + factor - 1),
+            );
+        }
+        // the middle of the block
+        tmp.anchors = self.map_anchors(|p| {
+            Point::new(p.x * factor + (factor - 1) / 2, p.y * factor + (factor - 1) / 2)
+        });
+        *self = tmp;
+    }
+
+    // a factor x factor block becomes a dot if any of its dots is set, so thin lines survive
+    pub fn scale_down(&mut self, factor: IndexType) {
+        if factor <= 1 {
+            return;
+        }
+        let max = self.max;
+        self.transform(Point::new(max.x / factor, max.y / factor), |p| {
+            Point::new(p.x / factor, p.y / factor)
+        });
+    }
+
+    /*
+        Clockwise rotation by any angle around the center of the sprite.
+        Every target dot takes the nearest source dot, so the result has no holes.
+        The sprite grows to fit the rotated shape.
+    */
+    pub fn rotate(&mut self, degrees: f32) {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        // the dots are unit squares, the turn goes around the middle of the box
+        let width = (self.max.x + 1) as f32;
+        let height = (self.max.y + 1) as f32;
+        let new_width = (width * cos.abs() + height * sin.abs()).round().max(1.0);
+        let new_height = (width * sin.abs() + height * cos.abs()).round().max(1.0);
+
+        let mut tmp = Self::new_with_size(new_width as IndexType, new_height as IndexType);
+        for y in 0..=tmp.max.y {
+            for x in 0..=tmp.max.x {
+                let dx = x as f32 + 0.5 - new_width / 2.0;
+                let dy = y as f32 + 0.5 - new_height / 2.0;
+                // back to the source with the opposite turn
+                let sx = dx * cos + dy * sin + width / 2.0;
+                let sy = -dx * sin + dy * cos + height / 2.0;
+                if sx >= 0.0 && sy >= 0.0 {
+                    let source = Point::new(sx as IndexType, sy as IndexType);
+                    if self.values.get(source.x, source.y) {
+                        tmp.draw_dot(x, y);
+                    }
+                }
+            }
+        }
+        tmp.anchors = self.map_anchors(|p| {
+            let dx = p.x as f32 + 0.5 - width / 2.0;
+            let dy = p.y as f32 + 0.5 - height / 2.0;
+            Point::new(
+                (dx * cos - dy * sin + new_width / 2.0 - 0.5).round() as IndexType,
+                (dx * sin + dy * cos + new_height / 2.0 - 0.5).round() as IndexType,
+            )
+        });
+        *self = tmp;
+    }
+
+    // x moves by kx for every row, y moves by ky for every column
+    pub fn shear(&mut self, kx: f32, ky: f32) {
+        let shift = |p: Point| {
+            Point::new(
+                p.x + (kx * p.y as f32).round() as IndexType,
+                p.y + (ky * p.x as f32).round() as IndexType,
+            )
+        };
+        let moved: Vec<Point> = self.values.iter().map(shift).collect();
+        let min_x = moved.iter().map(|p| p.x).min().unwrap_or(0);
+        let min_y = moved.iter().map(|p| p.y).min().unwrap_or(0);
         let mut tmp = Self::new();
+        for p in moved {
+            tmp.draw_dot(p.x - min_x, p.y - min_y);
+        }
+        tmp.anchors = self.map_anchors(|p| {
+            let moved = shift(p);
+            Point::new(moved.x - min_x, moved.y - min_y)
+        });
+        *self = tmp;
+    }
+
+    fn transform<F: Fn(Point) -> Point>(&mut self, max: Point, call: F) {
+        let mut tmp = Self::new_with_size(max.x + 1, max.y + 1);
         for p in self.values.iter() {
-            tmp.draw_dot(self.max.y - p.y, p.x);
+            let moved = call(p);
+            tmp.draw_dot(moved.x, moved.y);
         }
-        self.values = tmp.values;
-        self.max = tmp.max;
+        tmp.anchors = self.map_anchors(call);
+        *self = tmp;
+    }
+
+    fn map_anchors<F: Fn(Point) -> Point>(&self, call: F) -> BTreeMap<String, Point> {
+        self.anchors
+            .iter()
+            .map(|(name, p)| (name.clone(), call(*p)))
+            .collect()
     }
 
     pub fn draw_to_canvas(&self, canvas: &mut dyn Canvas, x: IndexType, y: IndexType) {
@@ -47,12 +255,152 @@ impl Sprite {
     }
 
     pub fn clean(&mut self, dot: &Point) {
-        self.values.remove(dot);
+        self.clean_dot(dot.x, dot.y);
+    }
+
+    // ORs the other sprite in at (x, y)
+    pub fn blit(&mut self, other: &Sprite, x: IndexType, y: IndexType) {
+        self.union(other, x, y);
+    }
+
+    /*
+        Set operations with the other sprite placed at (x, y).
+        union and xor grow the sprite to fit the other one. When the other one sticks out
+        left or above of (0, 0), the dots and the anchors of the sprite move right and down
+        to make room, like dilate does. difference and intersection keep the box and the anchors.
+    */
+    pub fn union(&mut self, other: &Sprite, x: IndexType, y: IndexType) {
+        let (x, y) = self.make_room(other, x, y);
+        self.values.blit(&other.values, x, y);
+        self.grow_to(other, x, y);
+    }
+
+    pub fn difference(&mut self, other: &Sprite, x: IndexType, y: IndexType) {
+        self.values.subtract(&other.values, x, y);
+        self.points.take();
+    }
+
+    pub fn intersection(&mut self, other: &Sprite, x: IndexType, y: IndexType) {
+        self.values.intersect(&other.values, x, y);
+        self.points.take();
+    }
+
+    pub fn xor(&mut self, other: &Sprite, x: IndexType, y: IndexType) {
+        let (x, y) = self.make_room(other, x, y);
+        self.values.toggle(&other.values, x, y);
+        self.grow_to(other, x, y);
+    }
+
+    /*
+        Morphology with a 3x3 square, radius times.
+        dilate grows the sprite by radius on every side and moves the dots and anchors by (radius, radius),
+        erode shrinks it back the same way, so dilate and erode undo each other's box.
+    */
+    pub fn dilate(&mut self, radius: IndexType) {
+        for _ in 0..radius {
+            let values = self.values.crop(0, 0, self.max.x as usize + 1, self.max.y as usize + 1).dilated();
+            self.replace_values(values, Point::new(self.max.x + 2, self.max.y + 2), 1);
+        }
+    }
+
+    // the dots with all the neighbours set survive
+    pub fn erode(&mut self, radius: IndexType) {
+        for _ in 0..radius {
+            let width = max(self.max.x - 1, 1);
+            let height = max(self.max.y - 1, 1);
+            let values = self.values.eroded().crop(1, 1, width as usize, height as usize);
+            self.replace_values(values, Point::new(width - 1, height - 1), -1);
+        }
+    }
+
+    // fills the gaps narrower than 2 * radius, the box stays the same
+    pub fn close(&mut self, radius: IndexType) {
+        self.dilate(radius);
+        self.erode(radius);
+    }
+
+    // the dots that touch the empty space, the box stays the same
+    pub fn outline(&mut self) {
+        let inner = self.values.eroded();
+        self.values.subtract(&inner, 0, 0);
+        self.points.take();
+    }
+
+    // the empty places that can not be reached from outside of the sprite become dots
+    pub fn fill_holes(&mut self) {
+        let width = self.max.x + 3;
+        let height = self.max.y + 3;
+        // the border around the sprite is the outside, the flood goes from there
+        let mut outside = BitMatrix::new(width as usize, height as usize);
+        let mut stack = vec![Point::new(0, 0)];
+        outside.set(0, 0);
+        while let Some(p) = stack.pop() {
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (x, y) = (p.x + dx, p.y + dy);
+                if x >= 0 && y >= 0 && x < width && y < height
+                    && !outside.get(x, y)
+                    && !self.values.get(x - 1, y - 1)
+                {
+                    outside.set(x, y);
+                    stack.push(Point::new(x, y));
+                }
+            }
+        }
+        for y in 0..=self.max.y {
+            for x in 0..=self.max.x {
+                if !outside.get(x + 1, y + 1) {
+                    self.values.set(x, y);
+                }
+            }
+        }
+        self.points.take();
+    }
+
+    // moves the sprite so the box of the other one placed at (x, y) starts inside of it
+    fn make_room(&mut self, other: &Sprite, x: IndexType, y: IndexType) -> (IndexType, IndexType) {
+        let (dx, dy) = (max(-x, 0), max(-y, 0));
+        if other.is_empty() || (dx == 0 && dy == 0) {
+            return (x, y);
+        }
+        let mut values = BitMatrix::new(
+            self.values.get_width() + dx as usize,
+            self.values.get_height() + dy as usize,
+        );
+        values.blit(&self.values, dx, dy);
+        self.values = values;
+        self.max = Point::new(self.max.x + dx, self.max.y + dy);
+        self.anchors = self.map_anchors(|p| Point::new(p.x + dx, p.y + dy));
+        self.points.take();
+        (x + dx, y + dy)
+    }
+
+    fn grow_to(&mut self, other: &Sprite, x: IndexType, y: IndexType) {
+        self.points.take();
+        if !other.is_empty() {
+            self.max.x = max(self.max.x, x + other.max.x);
+            self.max.y = max(self.max.y, y + other.max.y);
+        }
+    }
+
+    fn replace_values(&mut self, values: BitMatrix, max: Point, shift: IndexType) {
+        self.values = values;
+        self.max = max;
+        self.anchors = self.map_anchors(|p| Point::new(p.x + shift, p.y + shift));
+        self.points.take();
+    }
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl PointSet for Sprite {
     fn get_point_set(&self) -> Option<&HashSet<Point>> {
+        Some(self.points.get_or_init(|| self.values.iter().collect()))
+    }
+    fn get_bits(&self) -> Option<&BitMatrix> {
         Some(&self.values)
     }
     fn get_max(&self) -> Point {
@@ -64,14 +412,109 @@ impl PointSet for Sprite {
 }
 
 impl Canvas for Sprite {
+    /*
+        The sprite starts at (0, 0), dots left or above of it are not drawn and false is returned.
+        Use union to put a sprite in at a negative place, it moves the dots to make room.
+    */
     fn draw_dot(&mut self, x: IndexType, y: IndexType) -> bool {
-        self.values.insert((x, y).as_point());
+        if !self.values.set(x, y) {
+            return false;
+        }
+        self.points.take();
         self.max = (max(x, self.max.x), max(y, self.max.y)).as_point();
         true
     }
 
+    // true if there was a dot
     fn clean_dot(&mut self, x: IndexType, y: IndexType) -> bool {
-        self.values.remove(&(x, y).as_point());
+        if !self.values.get(x, y) {
+            return false;
+        }
+        self.values.clear(x, y);
+        self.points.take();
         true
     }
+
+    // the sprite is bounded by (0, 0) and its max point
+    fn get_dot(&self, x: IndexType, y: IndexType) -> Option<bool> {
+        if x >= 0 && y >= 0 && x <= self.max.x && y <= self.max.y {
+            Some(self.values.get(x, y))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dots(sprite: &Sprite) -> Vec<Point> {
+        sprite.values.iter().collect()
+    }
+
+    #[test]
+    fn negative_dots_are_not_drawn() {
+        let mut sprite = Sprite::new_from_string("+**");
+        assert!(!sprite.draw_dot(-1, 0));
+        assert!(!sprite.draw_dot(0, -1));
+        assert!(dots(&sprite) == vec![Point::new(0, 0), Point::new(1, 0)]);
+        assert!(sprite.get_max() == Point::new(1, 0));
+    }
+
+    #[test]
+    fn sprites_go_to_other_threads() {
+        fn send<T: Send>(_: T) {}
+        let sprite = Sprite::new_from_string("+**");
+        assert_eq!(sprite.get_point_set().map(|points| points.len()), Some(2));
+        send(sprite);
+    }
+
+    #[test]
+    fn union_left_and_above_moves_the_sprite() {
+        let mut sprite = Sprite::new_from_string("+**");
+        sprite.set_anchor(ANCHOR_PIVOT, Point::new(1, 0));
+        let other = Sprite::new_from_string("+*");
+        sprite.union(&other, -2, -1);
+        // nothing is lost, the old dots moved by (2, 1)
+        assert!(dots(&sprite) == vec![Point::new(0, 0), Point::new(2, 1), Point::new(3, 1)]);
+        assert!(sprite.get_max() == Point::new(3, 1));
+        assert!(sprite.get_anchor(ANCHOR_PIVOT) == Some(Point::new(3, 1)));
+    }
+
+    #[test]
+    fn union_inside_keeps_the_place() {
+        let mut sprite = Sprite::new_from_string("+*");
+        sprite.set_anchor(ANCHOR_PIVOT, Point::new(0, 0));
+        sprite.union(&Sprite::new_from_string("+*"), 2, 1);
+        assert!(dots(&sprite) == vec![Point::new(0, 0), Point::new(2, 1)]);
+        assert!(sprite.get_anchor(ANCHOR_PIVOT) == Some(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn clean_dot_tells_whether_there_was_a_dot() {
+        let mut sprite = Sprite::new_from_string("+* *");
+        assert!(sprite.clean_dot(0, 0));
+        assert!(!sprite.clean_dot(0, 0));
+        assert!(!sprite.clean_dot(1, 0));
+        assert!(!sprite.clean_dot(-1, 0));
+        assert!(!sprite.clean_dot(10, 10));
+        assert!(dots(&sprite) == vec![Point::new(2, 0)]);
+    }
+
+    #[test]
+    fn dsl_string_reads_back() {
+        use crate::sprite_parser::DslParser;
+
+        let mut sprite = Sprite::new_with_size(5, 5);
+        // empty rows above, between and below, empty columns left and between
+        sprite.draw_dot(1, 1);
+        sprite.draw_dot(4, 1);
+        sprite.draw_dot(2, 3);
+        assert_eq!(sprite.to_dsl_string(), "+\n| *  *\n|\n|  *\n|\n");
+        let read = DslParser::new().with_ragged_rows(true).parse_sprite(&sprite.to_dsl_string());
+        let read = read.unwrap();
+        assert!(dots(&read) == dots(&sprite));
+        assert!(read.get_max() == sprite.get_max());
+    }
 }
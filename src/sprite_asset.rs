@@ -0,0 +1,672 @@
+use std::{
+    error::Error,
+    fmt::{self, Write},
+    fs, io,
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    animated::{Animated, PlayMode},
+    point_set::PointSet,
+    position::{IndexType, Point},
+    sprite::{Sprite, ANCHOR_PIVOT},
+    sprite_parser::DslParser,
+};
+
+/*
+    Text format of the sprite assets. One file holds any number of named
+    animations, the frames use the same mini language as Canvas::draw_from_string.
+
+    # comment
+    animation explode       starts a new animation
+    loop once               once | loop | pingpong | static, once is the default
+    reverse                 plays the frames from the last one
+    count 3                 loop and pingpong stop after 3 loops
+    delay 100               default frame duration in milliseconds
+    anchor 4 4              the pivot, the point the animation turns around
+    anchor muzzle 4 0       any named point
+    frame                   starts a new frame with the default duration
+    +  X
+    | X X
+    frame 250               a frame shown for 250 milliseconds
+    anchor muzzle 5 0       an anchor inside a frame is only for this frame
+    size 7 2                the frame is bigger than its dots, only inside a frame
+    +X X X
+    frame                   a frame without rows is empty
+
+    Frame lines start with '+' or '|', leading spaces are ignored.
+    The frames are read by DslParser, any character but a space or a marker is a dot,
+    the rows may be ragged and the frames may be empty or of different sizes.
+    SpriteAsset is written back with to_string() in the same format.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoopMode {
+    Once,
+    Loop,
+    PingPong,
+    Static,
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::Io(err) => write!(f, "{}", err),
+            AssetError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for AssetError {}
+
+impl From<io::Error> for AssetError {
+    fn from(err: io::Error) -> Self {
+        AssetError::Io(err)
+    }
+}
+
+pub struct Frame {
+    pub sprite: Sprite,
+    // None means the default delay of the animation
+    pub delay: Option<Duration>,
+}
+
+pub struct AnimationAsset {
+    name: String,
+    loop_mode: LoopMode,
+    reverse: bool,
+    // None plays forever
+    play_count: Option<u32>,
+    delay: Duration,
+    anchors: Vec<(String, Point)>,
+    frames: Vec<Frame>,
+}
+
+impl AnimationAsset {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            loop_mode: LoopMode::Once,
+            reverse: false,
+            play_count: None,
+            delay: Duration::from_millis(100),
+            anchors: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_loop_mode(&self) -> LoopMode {
+        self.loop_mode
+    }
+
+    pub fn is_reverse(&self) -> bool {
+        self.reverse
+    }
+
+    pub fn get_play_count(&self) -> Option<u32> {
+        self.play_count
+    }
+
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn get_anchor(&self, name: &str) -> Option<Point> {
+        self.anchors.iter().find(|a| a.0 == name).map(|a| a.1)
+    }
+
+    pub fn get_frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    // the anchors that are the same in all frames go to the animation
+    pub fn from_animated(name: &str, animated: &Animated) -> Self {
+        let mut res = Self::new(name);
+        res.loop_mode = if animated.is_static() {
+            LoopMode::Static
+        } else {
+            match animated.get_play_mode() {
+                PlayMode::Once => LoopMode::Once,
+                PlayMode::Loop => LoopMode::Loop,
+                PlayMode::PingPong => LoopMode::PingPong,
+            }
+        };
+        if !animated.is_static() {
+            res.delay = animated.get_default_delay();
+            res.reverse = animated.is_reverse();
+            res.play_count = animated.get_play_count();
+        }
+        let sprites = animated.get_sprites();
+        if let Some(first) = sprites.first() {
+            for (name, point) in first.get_anchors() {
+                if sprites.iter().all(|s| s.get_anchor(name) == Some(point)) {
+                    res.anchors.push((name.to_string(), point));
+                }
+            }
+        }
+        for (sprite, delay) in sprites.iter().zip(animated.get_frame_delays()) {
+            res.frames.push(Frame {
+                sprite: sprite.clone(),
+                delay: *delay,
+            });
+        }
+        res
+    }
+
+    // every call makes a new animation, the asset stays untouched
+    pub fn build(&self) -> Animated {
+        let mut animated = match self.loop_mode {
+            LoopMode::Once => Animated::new(self.delay),
+            LoopMode::Loop => Animated::new_looped(self.delay),
+            LoopMode::PingPong => Animated::new_ping_pong(self.delay),
+            LoopMode::Static => Animated::new_static(),
+        };
+        animated.set_play_count(self.play_count);
+        for frame in self.frames.iter() {
+            let mut sprite = frame.sprite.clone();
+            for (name, point) in self.anchors.iter() {
+                if sprite.get_anchor(name).is_none() {
+                    sprite.set_anchor(name, *point);
+                }
+            }
+            match frame.delay {
+                Some(delay) => animated.add_sprite_with_delay(sprite, delay),
+                None => animated.add_sprite(sprite),
+            }
+        }
+        // the reversed playback starts from the last frame, the frames go first
+        animated.set_reverse(self.reverse);
+        animated
+    }
+}
+
+impl fmt::Display for AnimationAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "animation {}", self.name)?;
+        let mode = match self.loop_mode {
+            LoopMode::Once => "once",
+            LoopMode::Loop => "loop",
+            LoopMode::PingPong => "pingpong",
+            LoopMode::Static => "static",
+        };
+        writeln!(f, "loop {}", mode)?;
+        if self.loop_mode != LoopMode::Static {
+            writeln!(f, "delay {}", self.delay.as_millis())?;
+        }
+        if self.reverse {
+            writeln!(f, "reverse")?;
+        }
+        if let Some(count) = self.play_count {
+            writeln!(f, "count {}", count)?;
+        }
+        for (name, point) in self.anchors.iter() {
+            write_anchor(f, name, *point)?;
+        }
+        for frame in self.frames.iter() {
+            match frame.delay {
+                Some(delay) => writeln!(f, "frame {}", delay.as_millis())?,
+                None => writeln!(f, "frame")?,
+            }
+            let body = frame.sprite.to_dsl_string();
+            // the writer drops the empty edges, the size keeps them
+            let max = frame.sprite.get_max();
+            let written = frame_parser().parse_sprite(&body).map(|s| s.get_max());
+            if written.ok() != Some(max) {
+                writeln!(f, "size {} {}", max.x + 1, max.y + 1)?;
+            }
+            for (name, point) in frame.sprite.get_anchors() {
+                if self.get_anchor(name) != Some(point) {
+                    write_anchor(f, name, point)?;
+                }
+            }
+            f.write_str(&body)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_anchor(f: &mut fmt::Formatter<'_>, name: &str, point: Point) -> fmt::Result {
+    if name == ANCHOR_PIVOT {
+        writeln!(f, "anchor {} {}", point.x, point.y)
+    } else {
+        writeln!(f, "anchor {} {} {}", name, point.x, point.y)
+    }
+}
+
+#[derive(Default)]
+pub struct SpriteAsset {
+    animations: Vec<AnimationAsset>,
+}
+
+impl fmt::Display for SpriteAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (id, animation) in self.animations.iter().enumerate() {
+            if id > 0 {
+                f.write_char('\n')?;
+            }
+            write!(f, "{}", animation)?;
+        }
+        Ok(())
+    }
+}
+
+impl SpriteAsset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // replaces the animation with the same name
+    pub fn add(&mut self, animation: AnimationAsset) {
+        self.animations.retain(|a| a.name != animation.name);
+        self.animations.push(animation);
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssetError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, AssetError> {
+        let mut animations: Vec<AnimationAsset> = Vec::new();
+        // the body of the frame being read
+        let mut body: Option<FrameBody> = None;
+
+        for (id, raw) in text.lines().enumerate() {
+            let line = id + 1;
+            let trimmed = raw.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if trimmed.starts_with('+') || trimmed.starts_with('|') {
+                match body.as_mut() {
+                    Some(frame) => frame.lines.push((line, raw.trim_end_matches('\r').to_string())),
+                    None => return Err(parse_error(line, "frame line outside of a frame")),
+                }
+                continue;
+            }
+
+            let mut words = trimmed.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let args: Vec<&str> = words.collect();
+            if keyword == "animation" {
+                finish_frame(&mut animations, body.take())?;
+                match args.as_slice() {
+                    [name] => {
+                        if animations.iter().any(|a| a.name == *name) {
+                            return Err(parse_error(line, &format!("duplicate animation '{}'", name)));
+                        }
+                        animations.push(AnimationAsset::new(name));
+                    }
+                    _ => return Err(parse_error(line, "expected 'animation <name>'")),
+                }
+                continue;
+            }
+
+            let current = match animations.last_mut() {
+                Some(current) => current,
+                None => return Err(parse_error(line, &format!("'{}' before any animation", keyword))),
+            };
+            match (keyword, args.as_slice()) {
+                ("loop", [mode]) => {
+                    current.loop_mode = match *mode {
+                        "once" => LoopMode::Once,
+                        "loop" => LoopMode::Loop,
+                        "pingpong" => LoopMode::PingPong,
+                        "static" => LoopMode::Static,
+                        _ => return Err(parse_error(line, &format!("unknown loop mode '{}'", mode))),
+                    }
+                }
+                ("delay", [millis]) => current.delay = parse_millis(line, millis)?,
+                ("reverse", []) => current.reverse = true,
+                ("count", [count]) => match count.parse() {
+                    Ok(count) if count > 0 => current.play_count = Some(count),
+                    _ => return Err(parse_error(line, &format!("bad loop count '{}'", count))),
+                },
+                ("anchor", [x, y]) => {
+                    let point = Point::new(parse_index(line, x)?, parse_index(line, y)?);
+                    set_anchor(current, body.as_mut(), ANCHOR_PIVOT, point);
+                }
+                ("anchor", [name, x, y]) => {
+                    let point = Point::new(parse_index(line, x)?, parse_index(line, y)?);
+                    set_anchor(current, body.as_mut(), name, point);
+                }
+                ("size", [width, height]) => match body.as_mut() {
+                    Some(frame) => frame.size = Some((parse_index(line, width)?, parse_index(line, height)?)),
+                    None => return Err(parse_error(line, "'size' outside of a frame")),
+                },
+                ("frame", []) => {
+                    finish_frame(&mut animations, body.take())?;
+                    body = Some(FrameBody::new(line, None));
+                }
+                ("frame", [millis]) => {
+                    let delay = parse_millis(line, millis)?;
+                    finish_frame(&mut animations, body.take())?;
+                    body = Some(FrameBody::new(line, Some(delay)));
+                }
+                ("loop", _) | ("delay", _) | ("reverse", _) | ("count", _) | ("anchor", _) | ("size", _) | ("frame", _) => {
+                    return Err(parse_error(line, &format!("wrong arguments for '{}'", keyword)))
+                }
+                _ => return Err(parse_error(line, &format!("unknown keyword '{}'", keyword))),
+            }
+        }
+        finish_frame(&mut animations, body.take())?;
+        Ok(Self { animations })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AnimationAsset> {
+        self.animations.iter().find(|a| a.name == name)
+    }
+
+    pub fn get_all(&self) -> &[AnimationAsset] {
+        &self.animations
+    }
+
+    pub fn build(&self, name: &str) -> Option<Animated> {
+        self.get(name).map(|a| a.build())
+    }
+}
+
+// a frame while it is being read
+struct FrameBody {
+    // the rows with their lines in the file
+    lines: Vec<(usize, String)>,
+    delay: Option<Duration>,
+    anchors: Vec<(String, Point)>,
+    size: Option<(IndexType, IndexType)>,
+    // the line of the 'frame' keyword
+    line: usize,
+}
+
+impl FrameBody {
+    fn new(line: usize, delay: Option<Duration>) -> Self {
+        Self {
+            line,
+            lines: Vec::new(),
+            delay,
+            anchors: Vec::new(),
+            size: None,
+        }
+    }
+}
+
+fn set_anchor(current: &mut AnimationAsset, body: Option<&mut FrameBody>, name: &str, point: Point) {
+    let anchors = match body {
+        Some(frame) => &mut frame.anchors,
+        None => &mut current.anchors,
+    };
+    anchors.retain(|a| a.0 != name);
+    anchors.push((name.to_string(), point));
+}
+
+fn frame_parser() -> DslParser {
+    DslParser::new()
+        .with_ragged_rows(true)
+        .with_mixed_sizes(true)
+        .with_empty_frames(true)
+}
+
+fn finish_frame(animations: &mut [AnimationAsset], body: Option<FrameBody>) -> Result<(), AssetError> {
+    let (Some(current), Some(body)) = (animations.last_mut(), body) else {
+        return Ok(());
+    };
+    let first_line = current_line(&body);
+    let lines: Vec<&str> = body.lines.iter().map(|l| l.1.as_str()).collect();
+    // a frame without rows is empty, the size may still make it bigger
+    let mut sprite = if lines.is_empty() {
+        Sprite::new()
+    } else {
+        match frame_parser().parse_lines(&lines) {
+            Ok(mut frames) if frames.len() == 1 => frames.remove(0).0,
+            Ok(_) => {
+                return Err(parse_error(first_line, "one frame per 'frame', '-' is not needed"))
+            }
+            Err(err) => {
+                // back to the lines of the file
+                let line = body.lines.get(err.line - 1).map(|l| l.0).unwrap_or(first_line);
+                return Err(parse_error(line, &format!("column {}: {}", err.column, err.kind)));
+            }
+        }
+    };
+    if let Some((width, height)) = body.size {
+        let mut sized = Sprite::new_with_size(width, height);
+        sized.union(&sprite, 0, 0);
+        sprite = sized;
+    }
+    for (name, point) in body.anchors.iter() {
+        sprite.set_anchor(name, *point);
+    }
+    current.frames.push(Frame {
+        sprite,
+        delay: body.delay,
+    });
+    Ok(())
+}
+
+fn current_line(body: &FrameBody) -> usize {
+    body.lines.first().map(|l| l.0).unwrap_or(body.line)
+}
+
+fn parse_error(line: usize, message: &str) -> AssetError {
+    AssetError::Parse {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn parse_millis(line: usize, value: &str) -> Result<Duration, AssetError> {
+    value
+        .parse::<u64>()
+        .map(Duration::from_millis)
+        .map_err(|_| parse_error(line, &format!("bad duration '{}'", value)))
+}
+
+fn parse_index(line: usize, value: &str) -> Result<IndexType, AssetError> {
+    value
+        .parse::<IndexType>()
+        .map_err(|_| parse_error(line, &format!("bad coordinate '{}'", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::Canvas;
+
+    fn dots(sprite: &Sprite) -> Vec<Point> {
+        sprite.get_bits().map(|bits| bits.iter().collect()).unwrap_or_default()
+    }
+
+    fn error_line(text: &str) -> usize {
+        match SpriteAsset::parse(text) {
+            Err(AssetError::Parse { line, .. }) => line,
+            Err(err) => panic!("{}", err),
+            Ok(_) => panic!("{:?} parsed", text),
+        }
+    }
+
+    const ASSET: &str = r#"
+# two animations
+animation tank
+loop pingpong
+reverse
+count 3
+delay 40
+anchor 1 1
+anchor muzzle 1 0
+frame
++ *
+|***
+frame 250
+anchor muzzle 2 0
++  *
+|***
+
+animation boom
+frame
+size 4 3
+  +*
+"#;
+
+    #[test]
+    fn animations_with_their_settings() {
+        let asset = SpriteAsset::parse(ASSET).unwrap();
+        assert_eq!(asset.get_all().len(), 2);
+        let tank = asset.get("tank").unwrap();
+        assert_eq!(tank.get_loop_mode(), LoopMode::PingPong);
+        assert!(tank.is_reverse());
+        assert_eq!(tank.get_play_count(), Some(3));
+        assert_eq!(tank.get_delay(), Duration::from_millis(40));
+        assert!(tank.get_anchor(ANCHOR_PIVOT) == Some(Point::new(1, 1)));
+        assert!(tank.get_anchor("muzzle") == Some(Point::new(1, 0)));
+        let frames = tank.get_frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].delay, None);
+        assert_eq!(frames[1].delay, Some(Duration::from_millis(250)));
+        assert!(dots(&frames[0].sprite) == vec![
+            Point::new(1, 0),
+            Point::new(0, 1),
+            Point::new(1, 1),
+            Point::new(2, 1)
+        ]);
+        // the frame anchor is only in its frame
+        assert!(frames[0].sprite.get_anchor("muzzle").is_none());
+        assert!(frames[1].sprite.get_anchor("muzzle") == Some(Point::new(2, 0)));
+
+        let boom = asset.get("boom").unwrap();
+        assert_eq!(boom.get_loop_mode(), LoopMode::Once);
+        assert!(!boom.is_reverse());
+        assert_eq!(boom.get_play_count(), None);
+        assert_eq!(boom.get_delay(), Duration::from_millis(100));
+        assert!(boom.get_frames()[0].sprite.get_max() == Point::new(3, 2));
+        assert!(asset.get("none").is_none());
+    }
+
+    #[test]
+    fn build_fills_in_the_anchors() {
+        let tank = SpriteAsset::parse(ASSET).unwrap().build("tank").unwrap();
+        assert_eq!(tank.get_play_mode(), PlayMode::PingPong);
+        assert!(tank.is_reverse());
+        assert_eq!(tank.get_play_count(), Some(3));
+        assert_eq!(tank.get_frame_delays(), &[None, Some(Duration::from_millis(250))]);
+        let sprites = tank.get_sprites();
+        assert!(sprites[0].get_anchor("muzzle") == Some(Point::new(1, 0)));
+        assert!(sprites[1].get_anchor("muzzle") == Some(Point::new(2, 0)));
+        assert!(sprites.iter().all(|s| s.get_anchor(ANCHOR_PIVOT) == Some(Point::new(1, 1))));
+    }
+
+    #[test]
+    fn static_and_looped() {
+        let text = "animation a\nloop static\nframe\n+*\nanimation b\nloop loop\n";
+        let asset = SpriteAsset::parse(text).unwrap();
+        assert_eq!(asset.get("a").unwrap().get_loop_mode(), LoopMode::Static);
+        assert!(asset.build("a").unwrap().is_static());
+        assert_eq!(asset.get("b").unwrap().get_loop_mode(), LoopMode::Loop);
+        assert!(asset.get("b").unwrap().get_frames().is_empty());
+    }
+
+    #[test]
+    fn frames_without_rows_are_empty() {
+        let asset = SpriteAsset::parse("animation a\nframe\nframe\nsize 3 2\nframe\n+*").unwrap();
+        let frames = asset.get("a").unwrap().get_frames();
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].sprite.is_empty());
+        assert!(frames[0].sprite.get_max() == Point::new(0, 0));
+        assert!(frames[1].sprite.is_empty());
+        assert!(frames[1].sprite.get_max() == Point::new(2, 1));
+        assert!(dots(&frames[2].sprite) == vec![Point::new(0, 0)]);
+    }
+
+    #[test]
+    fn errors_report_the_file_line() {
+        assert_eq!(error_line("\nloop once"), 2);
+        assert_eq!(error_line("animation a\n\nloop twice"), 3);
+        assert_eq!(error_line("animation a\nanimation a"), 2);
+        assert_eq!(error_line("animation a\ncount 0"), 2);
+        assert_eq!(error_line("animation a\ndelay soon"), 2);
+        assert_eq!(error_line("animation a\nanchor 1"), 2);
+        assert_eq!(error_line("animation a\nsize 1 1"), 2);
+        assert_eq!(error_line("animation a\n+*"), 2);
+        assert_eq!(error_line("animation a\njump"), 2);
+        // the rows are checked by the parser, the line is the one in the file
+        assert_eq!(error_line("animation a\nframe\n# a comment\n+*\n\n|*\t"), 6);
+        assert_eq!(error_line("animation a\nframe\n+*\n|-"), 4);
+        let err = SpriteAsset::parse("animation a\nframe\n+*\n|-").err().unwrap();
+        let message = "line 4: column 2: '-' has to be alone on its line after a frame";
+        assert_eq!(err.to_string(), message);
+    }
+
+    fn assert_same(a: &SpriteAsset, b: &SpriteAsset) {
+        assert_eq!(a.get_all().len(), b.get_all().len());
+        for (a, b) in a.get_all().iter().zip(b.get_all()) {
+            assert_eq!(a.get_name(), b.get_name());
+            assert_eq!(a.get_loop_mode(), b.get_loop_mode());
+            assert_eq!(a.is_reverse(), b.is_reverse());
+            assert_eq!(a.get_play_count(), b.get_play_count());
+            assert_eq!(a.get_delay(), b.get_delay());
+            assert!(a.anchors == b.anchors);
+            assert_eq!(a.get_frames().len(), b.get_frames().len());
+            for (fa, fb) in a.get_frames().iter().zip(b.get_frames()) {
+                assert_eq!(fa.delay, fb.delay);
+                assert!(dots(&fa.sprite) == dots(&fb.sprite));
+                assert!(fa.sprite.get_max() == fb.sprite.get_max());
+                assert!(fa.sprite.get_anchors().eq(fb.sprite.get_anchors()));
+            }
+        }
+    }
+
+    #[test]
+    fn written_assets_read_back() {
+        let asset = SpriteAsset::parse(ASSET).unwrap();
+        let text = asset.to_string();
+        assert_same(&SpriteAsset::parse(&text).unwrap(), &asset);
+        // and again without a change
+        assert_eq!(SpriteAsset::parse(&text).unwrap().to_string(), text);
+    }
+
+    #[test]
+    fn animations_read_back() {
+        let mut walk = Animated::new_ping_pong(Duration::from_millis(30));
+        walk.set_reverse(true);
+        walk.set_play_count(Some(2));
+        let mut first = Sprite::new_from_string("+ *\n|***");
+        first.set_anchor(ANCHOR_PIVOT, Point::new(1, 1));
+        first.set_anchor("muzzle", Point::new(1, 0));
+        let mut second = first.clone();
+        second.set_anchor("muzzle", Point::new(2, 0));
+        walk.add_sprite(first);
+        walk.add_sprite_with_delay(second, Duration::from_millis(90));
+        // an empty frame and a frame with empty columns on the right
+        walk.add_sprite(Sprite::new());
+        let mut wide = Sprite::new_with_size(6, 2);
+        wide.draw_dot(0, 0);
+        wide.set_anchor(ANCHOR_PIVOT, Point::new(1, 1));
+        walk.add_sprite_with_delay(wide, Duration::from_millis(10));
+
+        let mut still = Animated::new_static();
+        still.add_sprite(Sprite::new_from_string("+**"));
+
+        let mut asset = SpriteAsset::new();
+        asset.add(AnimationAsset::from_animated("walk", &walk));
+        asset.add(AnimationAsset::from_animated("still", &still));
+        let read = SpriteAsset::parse(&asset.to_string()).unwrap();
+        assert_same(&read, &asset);
+
+        let built = read.build("walk").unwrap();
+        assert_eq!(built.get_frame_delays(), walk.get_frame_delays());
+        assert!(built.get_sprites()[3].get_max() == Point::new(5, 1));
+        assert!(built.get_sprites()[1].get_anchor("muzzle") == Some(Point::new(2, 0)));
+    }
+}
@@ -0,0 +1,450 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    canvas::Canvas,
+    position::{IndexType, Point},
+    sprite::Sprite,
+};
+
+/*
+    Strict reader of the Canvas::draw_from_string language.
+
+    +  *          '+' starts a frame, spaces before it are ignored
+    | ***         '|' starts every next row of the frame
+    -             a line with a single '-' ends the frame
+
+    Like in draw_from_string every other character is a dot, with_dots limits them
+    to a set so the typos are found. The markers and tabs are never dots.
+    Every frame is as wide as its longest row and as high as its rows,
+    empty lines are allowed only between the frames.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DslErrorKind {
+    UnknownChar(char),
+    Tab,
+    // a row that starts with something else than '+' or '|'
+    BadRowStart(char),
+    // '|' before any '+'
+    MissingFrameStart,
+    // '+' inside a frame, the '-' is missing
+    MissingFrameEnd,
+    // '+' or '|' inside a row
+    MarkerInRow(char),
+    // '-' inside a row or without a frame
+    StrayDash,
+    EmptyLineInFrame,
+    RaggedRow { expected: usize, found: usize },
+    EmptyFrame,
+    FrameSize { expected: (usize, usize), found: (usize, usize) },
+    NoFrames,
+    // parse_sprite got more than one frame
+    TooManyFrames,
+}
+
+impl fmt::Display for DslErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DslErrorKind::UnknownChar(c) => write!(f, "unknown character '{}'", c),
+            DslErrorKind::Tab => write!(f, "tabs are not allowed, use spaces"),
+            DslErrorKind::BadRowStart(c) => write!(f, "a row starts with '+' or '|', not '{}'", c),
+            DslErrorKind::MissingFrameStart => write!(f, "the frame does not start with '+'"),
+            DslErrorKind::MissingFrameEnd => write!(f, "'+' inside a frame, '-' is missing"),
+            DslErrorKind::MarkerInRow(c) => write!(f, "'{}' inside a row", c),
+            DslErrorKind::StrayDash => write!(f, "'-' has to be alone on its line after a frame"),
+            DslErrorKind::EmptyLineInFrame => write!(f, "empty line inside a frame, use '|'"),
+            DslErrorKind::RaggedRow { expected, found } => {
+                write!(f, "the row is {} wide, the frame is {} wide", found, expected)
+            }
+            DslErrorKind::EmptyFrame => write!(f, "the frame has no dots"),
+            DslErrorKind::FrameSize { expected, found } => write!(
+                f,
+                "the frame is {}x{}, the first one is {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            DslErrorKind::NoFrames => write!(f, "no frames"),
+            DslErrorKind::TooManyFrames => write!(f, "only one frame is expected"),
+        }
+    }
+}
+
+// line and column start from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DslError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: DslErrorKind,
+}
+
+impl fmt::Display for DslError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl Error for DslError {}
+
+pub struct DslParser {
+    // None takes any character
+    dots: Option<String>,
+    ragged_rows: bool,
+    mixed_sizes: bool,
+    empty_frames: bool,
+}
+
+// a frame while it is being read
+struct FrameRows {
+    line: usize,
+    // (line, length of the row, end column)
+    rows: Vec<(usize, usize, usize)>,
+    dots: Vec<Point>,
+}
+
+impl DslParser {
+    pub fn new() -> Self {
+        Self {
+            dots: None,
+            ragged_rows: false,
+            mixed_sizes: false,
+            empty_frames: false,
+        }
+    }
+
+    // the only characters that are dots, the others are errors
+    pub fn with_dots(mut self, dots: &str) -> Self {
+        self.dots = Some(dots.to_string());
+        self
+    }
+
+    // allows rows shorter than the frame, they end with empty dots
+    pub fn with_ragged_rows(mut self, value: bool) -> Self {
+        self.ragged_rows = value;
+        self
+    }
+
+    // allows frames of different sizes
+    pub fn with_mixed_sizes(mut self, value: bool) -> Self {
+        self.mixed_sizes = value;
+        self
+    }
+
+    // allows frames without dots
+    pub fn with_empty_frames(mut self, value: bool) -> Self {
+        self.empty_frames = value;
+        self
+    }
+
+    pub fn parse_sprite(&self, text: &str) -> Result<Sprite, DslError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut frames = self.parse_lines(&lines)?;
+        if frames.len() > 1 {
+            let line = frames[1].1;
+            return Err(error(line, 1, DslErrorKind::TooManyFrames));
+        }
+        Ok(frames.remove(0).0)
+    }
+
+    pub fn parse_frames(&self, text: &str) -> Result<Vec<Sprite>, DslError> {
+        let lines: Vec<&str> = text.lines().collect();
+        Ok(self.parse_lines(&lines)?.into_iter().map(|f| f.0).collect())
+    }
+
+    // the sprites with the line of their '+'
+    pub fn parse_lines(&self, lines: &[&str]) -> Result<Vec<(Sprite, usize)>, DslError> {
+        let mut frames: Vec<(Sprite, usize)> = Vec::new();
+        let mut sizes: Vec<(usize, usize)> = Vec::new();
+        let mut current: Option<FrameRows> = None;
+        let mut empty_line: Option<usize> = None;
+
+        for (id, raw) in lines.iter().enumerate() {
+            let line = id + 1;
+            let text = raw.strip_suffix('\r').unwrap_or(raw);
+            if text.trim().is_empty() && !text.contains('\t') {
+                empty_line = empty_line.or(Some(line));
+                continue;
+            }
+            let mut chars = text.chars().enumerate().skip_while(|(_, c)| *c == ' ');
+            let (start, marker) = match chars.next() {
+                Some((column, c)) => (column + 1, c),
+                None => continue,
+            };
+            match marker {
+                '-' => {
+                    if let Some((column, c)) = chars.find(|(_, c)| *c != ' ') {
+                        return Err(self.row_char_error(line, column + 1, c));
+                    }
+                    match current.take() {
+                        Some(frame) => self.finish(frame, &mut frames, &mut sizes)?,
+                        None => return Err(error(line, start, DslErrorKind::StrayDash)),
+                    }
+                }
+                '+' => {
+                    if current.is_some() {
+                        return Err(error(line, start, DslErrorKind::MissingFrameEnd));
+                    }
+                    current = Some(FrameRows {
+                        line,
+                        rows: Vec::new(),
+                        dots: Vec::new(),
+                    });
+                }
+                '|' => match current.as_ref() {
+                    None => return Err(error(line, start, DslErrorKind::MissingFrameStart)),
+                    Some(_) => {
+                        if let Some(empty) = empty_line {
+                            return Err(error(empty, 1, DslErrorKind::EmptyLineInFrame));
+                        }
+                    }
+                },
+                '\t' => return Err(error(line, start, DslErrorKind::Tab)),
+                c => return Err(error(line, start, DslErrorKind::BadRowStart(c))),
+            }
+            empty_line = None;
+            let Some(frame) = current.as_mut() else {
+                continue;
+            };
+
+            // the row itself
+            let y = frame.rows.len() as IndexType;
+            let mut length = 0;
+            for (column, c) in chars {
+                if c != ' ' {
+                    if !self.is_dot(c) {
+                        return Err(self.row_char_error(line, column + 1, c));
+                    }
+                    frame.dots.push(Point::new(length as IndexType, y));
+                }
+                length += 1;
+            }
+            frame.rows.push((line, length, start + length + 1));
+        }
+
+        if let Some(frame) = current.take() {
+            self.finish(frame, &mut frames, &mut sizes)?;
+        }
+        if frames.is_empty() {
+            return Err(error(lines.len().max(1), 1, DslErrorKind::NoFrames));
+        }
+        Ok(frames)
+    }
+
+    fn finish(
+        &self,
+        frame: FrameRows,
+        frames: &mut Vec<(Sprite, usize)>,
+        sizes: &mut Vec<(usize, usize)>,
+    ) -> Result<(), DslError> {
+        if frame.dots.is_empty() && !self.empty_frames {
+            return Err(error(frame.line, 1, DslErrorKind::EmptyFrame));
+        }
+        let width = frame.rows.iter().map(|r| r.1).max().unwrap_or(0);
+        let height = frame.rows.len();
+        if !self.ragged_rows {
+            if let Some(row) = frame.rows.iter().find(|r| r.1 != width) {
+                return Err(error(
+                    row.0,
+                    row.2,
+                    DslErrorKind::RaggedRow {
+                        expected: width,
+                        found: row.1,
+                    },
+                ));
+            }
+        }
+        if let Some(first) = sizes.first() {
+            if !self.mixed_sizes && *first != (width, height) {
+                return Err(error(
+                    frame.line,
+                    1,
+                    DslErrorKind::FrameSize {
+                        expected: *first,
+                        found: (width, height),
+                    },
+                ));
+            }
+        }
+        let mut sprite = Sprite::new_with_size(width as IndexType, height as IndexType);
+        for p in frame.dots.iter() {
+            sprite.draw_dot(p.x, p.y);
+        }
+        sizes.push((width, height));
+        frames.push((sprite, frame.line));
+        Ok(())
+    }
+
+    fn is_dot(&self, c: char) -> bool {
+        !matches!(c, '+' | '|' | '-' | '\t') && self.dots.as_ref().is_none_or(|d| d.contains(c))
+    }
+
+    fn row_char_error(&self, line: usize, column: usize, c: char) -> DslError {
+        let kind = match c {
+            '-' => DslErrorKind::StrayDash,
+            '+' | '|' => DslErrorKind::MarkerInRow(c),
+            '\t' => DslErrorKind::Tab,
+            _ => DslErrorKind::UnknownChar(c),
+        };
+        error(line, column, kind)
+    }
+}
+
+impl Default for DslParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn error(line: usize, column: usize, kind: DslErrorKind) -> DslError {
+    DslError { line, column, kind }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set::PointSet;
+
+    fn fails(parser: &DslParser, text: &str) -> DslError {
+        match parser.parse_frames(text) {
+            Ok(_) => panic!("{:?} parsed", text),
+            Err(err) => err,
+        }
+    }
+
+    fn at(line: usize, column: usize, kind: DslErrorKind) -> DslError {
+        error(line, column, kind)
+    }
+
+    fn dots(sprite: &Sprite) -> Vec<Point> {
+        sprite.get_bits().map(|bits| bits.iter().collect()).unwrap_or_default()
+    }
+
+    #[test]
+    fn frames_with_rows() {
+        let frames = DslParser::new().parse_lines(&["", "  + * ", "  |** ", "  -", "+ * ", "|** "]);
+        let frames = frames.unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].1, 2);
+        assert_eq!(frames[1].1, 5);
+        assert!(dots(&frames[0].0) == vec![Point::new(1, 0), Point::new(0, 1), Point::new(1, 1)]);
+        assert!(frames[0].0.get_max() == Point::new(2, 1));
+    }
+
+    #[test]
+    fn unknown_char() {
+        let parser = DslParser::new().with_dots("*");
+        assert_eq!(fails(&parser, "+***\n|*x*"), at(2, 3, DslErrorKind::UnknownChar('x')));
+        // without the set every other character is a dot
+        assert!(DslParser::new().parse_sprite("+***\n|*x*").is_ok());
+    }
+
+    #[test]
+    fn tab_and_bad_row_start() {
+        let parser = DslParser::new();
+        assert_eq!(fails(&parser, "+*\t*"), at(1, 3, DslErrorKind::Tab));
+        assert_eq!(fails(&parser, "+**\n *x"), at(2, 2, DslErrorKind::BadRowStart('*')));
+    }
+
+    #[test]
+    fn stray_dash() {
+        let parser = DslParser::new();
+        assert_eq!(fails(&parser, "  -\n+*"), at(1, 3, DslErrorKind::StrayDash));
+        assert_eq!(fails(&parser, "+*-"), at(1, 3, DslErrorKind::StrayDash));
+        assert_eq!(fails(&parser, "+*\n- *"), at(2, 3, DslErrorKind::UnknownChar('*')));
+    }
+
+    #[test]
+    fn marker_in_row() {
+        let parser = DslParser::new();
+        assert_eq!(fails(&parser, "+* |"), at(1, 4, DslErrorKind::MarkerInRow('|')));
+        assert_eq!(fails(&parser, "+*\n|+"), at(2, 2, DslErrorKind::MarkerInRow('+')));
+    }
+
+    #[test]
+    fn ragged_row() {
+        let kind = DslErrorKind::RaggedRow {
+            expected: 3,
+            found: 1,
+        };
+        // the column is right after the short row
+        assert_eq!(fails(&DslParser::new(), "+***\n  |*\n|* *"), at(2, 5, kind));
+    }
+
+    #[test]
+    fn empty_frame() {
+        let parser = DslParser::new();
+        assert_eq!(fails(&parser, "+*\n-\n+  \n|  "), at(3, 1, DslErrorKind::EmptyFrame));
+    }
+
+    #[test]
+    fn frame_size() {
+        let kind = DslErrorKind::FrameSize {
+            expected: (1, 1),
+            found: (2, 1),
+        };
+        assert_eq!(fails(&DslParser::new(), "+*\n-\n\n+**"), at(4, 1, kind));
+    }
+
+    #[test]
+    fn missing_frame_start_and_end() {
+        let parser = DslParser::new();
+        assert_eq!(fails(&parser, "  |*"), at(1, 3, DslErrorKind::MissingFrameStart));
+        assert_eq!(fails(&parser, "+*\n-\n|*"), at(3, 1, DslErrorKind::MissingFrameStart));
+        assert_eq!(fails(&parser, "+*\n +*"), at(2, 2, DslErrorKind::MissingFrameEnd));
+    }
+
+    #[test]
+    fn empty_line_in_frame() {
+        let parser = DslParser::new();
+        assert_eq!(fails(&parser, "+*\n\n  \n|*"), at(2, 1, DslErrorKind::EmptyLineInFrame));
+        // empty lines after the last row end the frame
+        assert!(parser.parse_sprite("+*\n\n").is_ok());
+    }
+
+    #[test]
+    fn no_frames() {
+        let parser = DslParser::new();
+        assert_eq!(fails(&parser, ""), at(1, 1, DslErrorKind::NoFrames));
+        assert_eq!(fails(&parser, "\n  \n"), at(2, 1, DslErrorKind::NoFrames));
+    }
+
+    #[test]
+    fn too_many_frames() {
+        let err = DslParser::new().parse_sprite("+*\n-\n+*").err();
+        assert_eq!(err, Some(at(3, 1, DslErrorKind::TooManyFrames)));
+    }
+
+    #[test]
+    fn ragged_rows_end_with_empty_dots() {
+        let parser = DslParser::new().with_ragged_rows(true);
+        let frames = parser.parse_frames("+***\n|*\n| *").unwrap();
+        assert!(frames[0].get_max() == Point::new(2, 2));
+        assert!(dots(&frames[0]) == vec![
+            Point::new(0, 0),
+            Point::new(1, 0),
+            Point::new(2, 0),
+            Point::new(0, 1),
+            Point::new(1, 2)
+        ]);
+    }
+
+    #[test]
+    fn mixed_sizes_keep_their_size() {
+        let parser = DslParser::new().with_mixed_sizes(true);
+        let frames = parser.parse_frames("+*\n-\n+**\n|**").unwrap();
+        assert!(frames[0].get_max() == Point::new(0, 0));
+        assert!(frames[1].get_max() == Point::new(1, 1));
+        // the rows still have to match inside a frame
+        let kind = DslErrorKind::RaggedRow {
+            expected: 2,
+            found: 1,
+        };
+        assert_eq!(fails(&parser, "+*\n-\n+**\n|*"), at(4, 3, kind));
+    }
+
+    #[test]
+    fn empty_frames_keep_their_size() {
+        let parser = DslParser::new().with_empty_frames(true);
+        let frames = parser.parse_frames("+*\n|*\n-\n+ \n| ").unwrap();
+        assert!(frames[1].is_empty());
+        assert!(frames[1].get_max() == Point::new(0, 1));
+    }
+}
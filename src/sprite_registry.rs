@@ -0,0 +1,81 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{animated::Animated, direction::Direction, sprite_asset::SpriteAsset};
+
+/*
+    Animations shared by all the objects that show them.
+    Every animation is parsed once and turned to the four directions once,
+    get hands out clones that share the frames with the registry.
+    The animations are added looking up.
+    The names are namespaced by the kind of the object, like "tank/idle".
+*/
+
+// one registry for the whole game, every object keeps a handle to it
+pub type SharedRegistry = Rc<RefCell<SpriteRegistry>>;
+
+pub struct SpriteRegistry {
+    // indexed by the quarter turns of the direction
+    animations: HashMap<String, [Animated; 4]>,
+}
+
+impl SpriteRegistry {
+    pub fn new() -> Self {
+        Self {
+            animations: HashMap::new(),
+        }
+    }
+
+    pub fn new_shared() -> SharedRegistry {
+        Rc::new(RefCell::new(Self::new()))
+    }
+
+    // replaces the animation with the same name
+    pub fn insert(&mut self, name: &str, animated: Animated) {
+        self.animations.insert(name.to_string(), turn_all(animated));
+    }
+
+    // every animation of the asset under its own name
+    pub fn insert_asset(&mut self, asset: &SpriteAsset) {
+        for animation in asset.get_all() {
+            self.insert(animation.get_name(), animation.build());
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.animations.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str, dir: Direction) -> Option<Animated> {
+        self.animations
+            .get(name)
+            .map(|turns| turns[dir.get_quarter_turns() as usize].clone())
+    }
+
+    // makes the animation only the first time it is asked for
+    pub fn get_or_insert_with<F>(&mut self, name: &str, dir: Direction, make: F) -> Animated
+    where
+        F: FnOnce() -> Animated,
+    {
+        // the lookup does not allocate the key, only the insert does
+        if !self.contains(name) {
+            self.insert(name, make());
+        }
+        self.animations[name][dir.get_quarter_turns() as usize].clone()
+    }
+}
+
+impl Default for SpriteRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn turn_all(up: Animated) -> [Animated; 4] {
+    let turn = |turns: i32| {
+        let mut animated = up.clone();
+        animated.rotate_quarters(turns);
+        animated
+    };
+    let (right, down, left) = (turn(1), turn(2), turn(3));
+    [up, right, down, left]
+}
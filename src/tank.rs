@@ -1,29 +1,46 @@
 use std::{time::Duration, collections::HashSet};
 
 use crate::{
+    animated::{Animated, PlayMode},
+    animation_controller::{AnimationController, AnimationState},
+    bit_matrix::BitMatrix,
     animation_builder::AnimationBuilder,
-    canvas::Canvas,
+    color::Color,
     drawable::Drawable,
     game_object::game_object::GameObject,
     obstacle::{Obstacles, Obstacle},
     position::{AsPoint, IndexType, Point},
     shot::Shot,
-    sprite::Sprite,
+    sprite::{Sprite, ANCHOR_MUZZLE, ANCHOR_PIVOT},
+    sprite_parser::DslParser,
+    sprite_registry::{SharedRegistry, SpriteRegistry},
     timer::Timer, 
     direction::Direction, 
     point_set::PointSet, 
-    game_object::{GameObjectArea, GameObjectAnimated}
+    game_object::{GameObjectArea, GameObjectControlled}
 };
 
+/*
+    The tank keeps an Rc of the shared registry,
+    so it stays on the thread that made it (it is not Send).
+*/
 pub struct Tank {
-    area: GameObjectAnimated,
-    border: Sprite,
+    area: GameObjectControlled,
+    // the hitbox, a single frame turned with the tank
+    border: Animated,
+    // the frames of every kind of tank and shot, shared by the whole game
+    assets: SharedRegistry,
+    // the registry names of the animations, indexed by AnimationState::index
+    asset_names: [Option<String>; AnimationState::ALL.len()],
+    border_name: String,
     direction: Direction,
+    // every shot starts as a clone of it
+    shot: AnimationController,
     shots: Vec<Shot>,
     recharge_delay: Timer,
 }
 
-const TANK_SPRITE: &'static str = r#"
+const TANK_SPRITE: &str = r#"
 +    *
 |    *
 |  *****
@@ -55,26 +72,135 @@ const TANK_SPRITE: &'static str = r#"
 |   *** 
 "#;
 
+// the barrel is pulled in after a shot
+const TANK_RECOIL_DELAY: Duration = Duration::from_millis(80);
+
+// the built-in art is written by hand, its rows and frames differ in length
+fn tank_parser() -> DslParser {
+    DslParser::new().with_ragged_rows(true).with_mixed_sizes(true)
+}
+
+// the kind of the built-in tank
+pub const TANK_KIND: &str = "tank";
+
+const IDLE_ASSET: &str = "idle";
+const MOVE_ASSET: &str = "move";
+const FIRE_ASSET: &str = "fire";
+const BORDER_ASSET: &str = "border";
+
+fn asset_name(kind: &str, asset: &str) -> String {
+    format!("{}/{}", kind, asset)
+}
+
 impl Tank {
+    // a tank with a registry of its own, new_with_registry shares the sprites between tanks
     pub fn new(x: IndexType, y: IndexType) -> Self {
-        let tank_animated = AnimationBuilder::new_static()
-            .add_from_string(TANK_SPRITE)
-            .build();
-        let mut a = Sprite::new();
-        for x in 0..=tank_animated.get_max().x {
-            for y in 0..=tank_animated.get_max().y {
-                a.draw_dot(x, y);
-            }
+        Self::new_with_registry(x, y, &SpriteRegistry::new_shared())
+    }
+
+    // the built-in art is parsed by the first tank, the next ones share it
+    pub fn new_with_registry(x: IndexType, y: IndexType, assets: &SharedRegistry) -> Self {
+        if !Self::has_kind(&assets.borrow(), TANK_KIND) {
+            let tank_animated = AnimationBuilder::new_static()
+                .try_add_from_string(TANK_SPRITE, &tank_parser())
+                .expect("the built-in tank sprite is broken")
+                .build();
+            Self::register_kind(&mut assets.borrow_mut(), TANK_KIND, tank_animated);
+        }
+        Self::new_of_kind(x, y, TANK_KIND, assets)
+    }
+
+    /*
+        Adds the animations of a kind of tank to the registry, under names starting with `kind`.
+        The animation has to look up, the registry turns it.
+        Without the pivot and the muzzle anchors the tank turns around the middle of all its frames
+        and shoots from the middle of their top side.
+    */
+    pub fn register_kind(assets: &mut SpriteRegistry, kind: &str, mut tank_animated: Animated) {
+        // the hitbox covers every frame, the gaps between the track links are closed
+        let mut border = tank_animated.get_silhouette();
+        let max = border.get_max();
+        if tank_animated.get_anchor(ANCHOR_PIVOT).is_none() {
+            tank_animated.set_anchor(ANCHOR_PIVOT, (max.x / 2, max.y / 2).as_point());
         }
+        if tank_animated.get_anchor(ANCHOR_MUZZLE).is_none() {
+            tank_animated.set_anchor(ANCHOR_MUZZLE, (max.x / 2, 0).as_point());
+        }
+        border.close(1);
+        border.fill_holes();
+        Self::register_assets(assets, kind, tank_animated, border);
+    }
+
+    pub fn has_kind(assets: &SpriteRegistry, kind: &str) -> bool {
+        assets.contains(&asset_name(kind, IDLE_ASSET))
+    }
+
+    // a kind added with register_kind, the missing animations are left empty
+    pub fn new_of_kind(x: IndexType, y: IndexType, kind: &str, assets: &SharedRegistry) -> Self {
+        let mut asset_names: [Option<String>; AnimationState::ALL.len()] = Default::default();
+        for state in AnimationState::ALL {
+            asset_names[state.index()] = Self::get_asset_name(state).map(|a| asset_name(kind, a));
+        }
+        let border_name = asset_name(kind, BORDER_ASSET);
+        let shot = Shot::template(&mut assets.borrow_mut());
+        let registry = assets.borrow();
+        let get = |name: Option<&str>| {
+            name.and_then(|name| registry.get(name, Direction::Up))
+                .unwrap_or_else(Animated::new_static)
+        };
+        let animation = |state: AnimationState| get(asset_names[state.index()].as_deref());
+        let idle = animation(AnimationState::Idle);
+        let controller = AnimationController::new(AnimationState::Idle, idle)
+            .with_state(AnimationState::Move, animation(AnimationState::Move))
+            .with_state(AnimationState::Fire, animation(AnimationState::Fire))
+            .with_next(AnimationState::Fire, AnimationState::Idle);
+        let border = get(Some(&border_name));
+        drop(registry);
         Self {
-            area: GameObjectAnimated::new(tank_animated, x, y),
-            border: a,
+            area: GameObjectControlled::new(controller, x, y),
+            border,
+            assets: assets.clone(),
+            asset_names,
+            border_name,
             direction: Direction::Up,
+            shot,
             shots: Vec::new(),
             recharge_delay: Timer::new(Duration::from_millis(250)),
         }
     }
 
+    /*
+        Idle is the animation itself, Move loops the same frames
+        and every step of the tank rolls them by one, Fire is the first frame without the muzzle dot.
+    */
+    fn register_assets(assets: &mut SpriteRegistry, kind: &str, idle: Animated, border: Sprite) {
+        let mut moving = idle.clone();
+        moving.set_play_mode(PlayMode::Loop);
+        let mut fire = Animated::new(TANK_RECOIL_DELAY);
+        if let Some(first) = idle.get_sprites().first() {
+            let mut recoil = first.clone();
+            if let Some(muzzle) = recoil.get_anchor(ANCHOR_MUZZLE) {
+                recoil.clean(&muzzle);
+            }
+            fire.add_sprite(recoil);
+        }
+        assets.insert(&asset_name(kind, IDLE_ASSET), idle);
+        assets.insert(&asset_name(kind, MOVE_ASSET), moving);
+        assets.insert(&asset_name(kind, FIRE_ASSET), fire);
+        let mut hitbox = Animated::new_static();
+        hitbox.add_sprite(border);
+        assets.insert(&asset_name(kind, BORDER_ASSET), hitbox);
+    }
+
+    fn get_asset_name(state: AnimationState) -> Option<&'static str> {
+        match state {
+            AnimationState::Idle => Some(IDLE_ASSET),
+            AnimationState::Move => Some(MOVE_ASSET),
+            AnimationState::Fire => Some(FIRE_ASSET),
+            _ => None,
+        }
+    }
+
     pub fn update(&mut self, delta: Duration) {
         self.area.sprite.update(delta);
         for s in self.shots.iter_mut() {
@@ -86,10 +212,46 @@ impl Tank {
         self.recharge_delay.update(delta);
     }
 
+    pub fn set_color(&mut self, color: Color) {
+        self.area.set_color(Some(color));
+    }
+
     pub fn rotate_90(&mut self) {
-        self.area.sprite.rotate_90();
-        self.border.rotate_90();
-        self.direction = self.direction.next_clockwise();
+        self.turn_to(self.direction.next_clockwise());
+    }
+
+    /*
+        Turns around the pivot, so the pivot stays at the same place of the world.
+        The turned frames come from the registry, the animations keep playing.
+    */
+    pub fn turn_to(&mut self, dir: Direction) {
+        let before = self.get_anchor_pos(ANCHOR_PIVOT);
+        let assets = self.assets.borrow();
+        let names = &self.asset_names;
+        self.area.sprite.for_each_state(|state, animated| {
+            let name = names[state.index()].as_deref();
+            if let Some(turned) = name.and_then(|name| assets.get(name, dir)) {
+                animated.set_frames_from(&turned);
+            }
+        });
+        if let Some(border) = assets.get(&self.border_name, dir) {
+            self.border.set_frames_from(&border);
+        }
+        drop(assets);
+        self.direction = dir;
+        if let (Some(before), Some(after)) = (before, self.get_anchor_pos(ANCHOR_PIVOT)) {
+            let pos = self.area.get_pos();
+            self.area.move_to(pos.x + before.x - after.x, pos.y + before.y - after.y);
+        }
+    }
+
+    // the anchor of the current frame in the world coordinates
+    pub fn get_anchor_pos(&self, name: &str) -> Option<Point> {
+        let pos = self.area.get_pos();
+        self.area
+            .sprite
+            .get_anchor(name)
+            .map(|p| (pos.x + p.x, pos.y + p.y).as_point())
     }
 
     pub fn go_left(&mut self) {
@@ -109,15 +271,13 @@ impl Tank {
     }
 
     fn goto(&mut self, dir: Direction) { 
-        let mut turned = false;
-        while self.direction != dir {
-            turned = true;
-            self.rotate_90();
-        }
-        if !turned {
+        if self.direction != dir {
+            self.turn_to(dir);
+        } else {
             let fixed_pos = dir.go_forward(self.get_pos());
             self.area.move_to(fixed_pos.x, fixed_pos.y);
-            self.area.sprite.update_force();    
+            self.area.sprite.request(AnimationState::Move);
+            self.area.sprite.update_force();
         }
     }
 
@@ -129,8 +289,11 @@ impl Tank {
     pub fn shoot(&mut self) {
         if self.recharge_delay.ready() {
             let center = self.get_front_center();
-            self.shots
-                .push(Shot::new(center.x, center.y, self.direction));
+            let assets = self.assets.borrow();
+            let shot = Shot::new(center.x, center.y, self.direction, &self.shot, &assets);
+            drop(assets);
+            self.shots.push(shot);
+            self.area.sprite.request(AnimationState::Fire);
             self.recharge_delay.reset()
         }
     }
@@ -141,15 +304,14 @@ impl Tank {
         }
     }
 
+    pub fn get_shots_count(&self) -> usize {
+        self.shots.len()
+    }
+
+    // the muzzle, where the shots come from
     pub fn get_front_center(&self) -> Point {
-        let pos = self.area.get_pos();
-        let spos = (self.get_width() / 2, self.get_height() / 2);
-        match self.direction {
-            Direction::Up => (spos.0 + pos.x, pos.y).as_point(),
-            Direction::Right => (spos.0 + pos.x, spos.1 + pos.y).as_point(),
-            Direction::Down => (spos.0 + pos.x, spos.1 + pos.y).as_point(),
-            Direction::Left => (pos.x, spos.1 + pos.y).as_point(),
-        }
+        self.get_anchor_pos(ANCHOR_MUZZLE)
+            .unwrap_or_else(|| self.area.get_pos())
     }
 
     fn check_shots_obstacle(&mut self, o: &mut Obstacle) {
@@ -170,15 +332,8 @@ impl Tank {
 
     pub fn check_obstacles(&mut self, obstacles: &mut Obstacles) {
         for o in obstacles.get_all_mut().iter_mut() {
-            let tank_overlap = self.get_overlap(o);
-            if !tank_overlap.is_empty() {
-                if !o.is_transparent() {
-                    self.go_back();
-                } else {
-                    if o.is_ground() {
-                        o.set_invisivle_dots(tank_overlap.iter().map(|p| p.1).collect());
-                    }
-                }
+            if !o.is_transparent() && self.is_overlapping(o) {
+                self.go_back();
             }
             self.check_shots_obstacle(o);
         }
@@ -187,6 +342,13 @@ impl Tank {
 
 impl Drawable for Tank {
     fn draw(&self, canvas: &mut dyn crate::canvas::Canvas) {
+        // the whole hull hides whatever is on the ground under it
+        let pos = self.area.get_pos();
+        if let Some(bits) = self.border.get_bits() {
+            for p in bits.iter() {
+                canvas.cover_dot(pos.x + p.x, pos.y + p.y);
+            }
+        }
         self.area.draw_to_canvas(canvas);
         for s in self.shots.iter() {
             s.draw(canvas);
@@ -201,4 +363,35 @@ impl GameObject for Tank {
     fn get_point_set(&self) -> Option<&HashSet<Point>> {
         self.border.get_point_set()
     }
+    fn get_bits(&self) -> Option<&BitMatrix> {
+        self.border.get_bits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_anchors_cover_every_frame() {
+        // the first frame is smaller than the second one
+        let animated = AnimationBuilder::new_static()
+            .try_add_from_string("+*\n-\n+*****\n|*   *\n|*****", &tank_parser())
+            .unwrap()
+            .build();
+        let assets = SpriteRegistry::new_shared();
+        Tank::register_kind(&mut assets.borrow_mut(), "small", animated);
+        let tank = Tank::new_of_kind(10, 10, "small", &assets);
+        assert!(tank.get_anchor_pos(ANCHOR_PIVOT) == Some(Point::new(12, 11)));
+        assert!(tank.get_front_center() == Point::new(12, 10));
+    }
+
+    #[test]
+    fn tanks_share_the_registry() {
+        let assets = SpriteRegistry::new_shared();
+        let first = Tank::new_with_registry(0, 0, &assets);
+        let second = Tank::new_with_registry(20, 0, &assets);
+        assert!(first.border.shares_frames(&second.border));
+        assert_eq!(std::rc::Rc::strong_count(&assets), 3);
+    }
 }
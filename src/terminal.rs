@@ -1,25 +1,36 @@
 use std::{
     error::Error,
-    io::{self, Stdout},
+    io::{self, Stdout, Write},
 };
 
 use crossterm::{
     cursor::{Hide, Show},
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand,
+    ExecutableCommand, QueueableCommand,
 };
 
 pub fn init() -> Result<Stdout, Box<dyn Error>> {
     let mut stdout = io::stdout();
     terminal::enable_raw_mode()?;
-    stdout.execute(EnterAlternateScreen)?;
-    stdout.execute(Hide)?;
+    write_init(&mut stdout)?;
     Ok(stdout)
 }
 
+// what init sends to the terminal, recorders replay it before the first frame
+pub fn write_init<W: Write>(out: &mut W) -> io::Result<()> {
+    out.queue(EnterAlternateScreen)?;
+    out.queue(Hide)?;
+    out.flush()
+}
+
 pub fn deinit(stdout: &mut Stdout) -> Result<(), Box<dyn Error>> {
     terminal::disable_raw_mode()?;
     stdout.execute(LeaveAlternateScreen)?;
     stdout.execute(Show)?;
     Ok(())
 }
+
+// (columns, rows)
+pub fn size() -> Result<(u16, u16), Box<dyn Error>> {
+    Ok(terminal::size()?)
+}
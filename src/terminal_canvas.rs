@@ -0,0 +1,22 @@
+use crate::{canvas::Canvas, color::CellColor};
+
+/*
+    a canvas that maps its dots onto terminal cells.
+    the renderer works with any of them.
+*/
+pub trait TerminalCanvas: Canvas {
+    // size in dots
+    fn new(x: usize, y: usize) -> Self
+    where
+        Self: Sized;
+
+    // empties all the cells and resets their colors, keeps the memory
+    fn clear(&mut self);
+
+    // size in terminal cells (columns, rows)
+    fn get_cells_size(&self) -> (usize, usize);
+    // how many dots a single cell holds (columns, rows)
+    fn get_cell_dots(&self) -> (usize, usize);
+    fn get_cell_char(&self, x: usize, y: usize) -> char;
+    fn get_cell_color(&self, x: usize, y: usize) -> CellColor;
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use tank::{
+    braille_canvas::BrailleCanvas,
+    drawable::Drawable,
+    headless_canvas::HeadlessCanvas,
+    obstacle::{Obstacle, Obstacles},
+    snapshot::{assert_snapshot, headless_to_string, to_cells_string},
+    tank::Tank,
+};
+
+/*
+    Scenes built from the game objects, compared with the frames in tests/golden.
+    Run with UPDATE_SNAPSHOTS=1 to write the frames again after a wanted change.
+*/
+
+fn golden(name: &str) -> String {
+    format!("{}/tests/golden/{}.txt", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn obstacles() -> Obstacles {
+    let mut obstacles = Obstacles::new();
+    obstacles.add_obstacle(Obstacle::new_rect(0, 0, 39, 3));
+    obstacles.add_obstacle(Obstacle::new_circle(30, 20, 5));
+    obstacles.add_obstacle(Obstacle::new_frame(0, 0, 39, 31));
+    obstacles
+}
+
+#[test]
+fn tank_alone() {
+    let tank = Tank::new(2, 2);
+    let mut canvas = HeadlessCanvas::new(14, 14);
+    tank.draw(&mut canvas);
+    assert_snapshot(&headless_to_string(&canvas), golden("tank_alone"));
+}
+
+#[test]
+fn tank_turned_right() {
+    let mut tank = Tank::new(2, 2);
+    tank.go_right();
+    let mut canvas = HeadlessCanvas::new(14, 14);
+    tank.draw(&mut canvas);
+    assert_snapshot(&headless_to_string(&canvas), golden("tank_turned_right"));
+}
+
+#[test]
+fn tank_among_obstacles() {
+    let tank = Tank::new(8, 12);
+    let obstacles = obstacles();
+    let mut canvas = HeadlessCanvas::new(40, 32);
+    obstacles.draw(&mut canvas);
+    tank.draw(&mut canvas);
+    assert_snapshot(&headless_to_string(&canvas), golden("tank_among_obstacles"));
+}
+
+#[test]
+fn shot_in_flight() {
+    let mut tank = Tank::new(8, 20);
+    // the gun is loaded after the recharge delay
+    tank.update(Duration::from_millis(300));
+    tank.shoot();
+    // a few steps away from the muzzle
+    for _ in 0..16 {
+        tank.update(Duration::from_millis(15));
+    }
+    assert_eq!(tank.get_shots_count(), 1);
+    let mut canvas = HeadlessCanvas::new(26, 32);
+    tank.draw(&mut canvas);
+    assert_snapshot(&headless_to_string(&canvas), golden("shot_in_flight"));
+}
+
+#[test]
+fn shot_exploding_on_obstacle() {
+    let mut tank = Tank::new(8, 20);
+    let mut obstacles = obstacles();
+    tank.update(Duration::from_millis(300));
+    tank.shoot();
+    // flies up to the wall and blows up there, the explosion is half way
+    for _ in 0..45 {
+        tank.update(Duration::from_millis(15));
+        tank.check_obstacles(&mut obstacles);
+    }
+    let mut canvas = HeadlessCanvas::new(40, 32);
+    obstacles.draw(&mut canvas);
+    tank.draw(&mut canvas);
+    assert_snapshot(&headless_to_string(&canvas), golden("shot_exploding_on_obstacle"));
+}
+
+#[test]
+fn braille_cells() {
+    let tank = Tank::new(8, 12);
+    let obstacles = obstacles();
+    let mut canvas = BrailleCanvas::new(40, 32);
+    obstacles.draw(&mut canvas);
+    tank.draw(&mut canvas);
+    assert_snapshot(&to_cells_string(&canvas), golden("braille_cells"));
+}